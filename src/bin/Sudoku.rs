@@ -42,7 +42,7 @@ struct Choice {
         val: ValT,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum Constraint {
         Row (RowT, ValT),
         Column (ColT, ValT),
@@ -166,6 +166,17 @@ fn choice_satisies_constraint (choice: &Choice, constraint: &Constraint) -> bool
 
 
 
+// The constraints that a choice satisfies, so that we only build the nodes that exist.
+fn choice_constraints (choice: &Choice) -> [Constraint; 4]
+{
+        let Choice {row: r, col: c, val: v} = *choice;
+        let constraints = [Constraint::Row(r, v), Constraint::Column(c, v), Constraint::Box(to_box_nr(r, c), v), Constraint::Position(r, c)];
+        debug_assert!(constraints.iter().all(|cst| choice_satisies_constraint(choice, cst)));
+        constraints
+}
+
+
+
 fn choice_satisies_extra_constraint (choice: &Choice, extra: &ConstraintExtra) -> bool
 {
         match *extra {
@@ -458,7 +469,7 @@ fn solve_board (b_str: String ,time_it: bool)
         let rows: &[Choice] = EMPTY_POSSIBLE_CHOICES.as_ref();
         let cols: &[Constraint] = EMPTY_CONSTRAINTS.as_ref();
         let made_choices = all_current_choices(&board);
-        let mut solver = dlx::UCSolver::from_pred(rows, cols, choice_satisies_constraint);
+        let mut solver = dlx::UCSolver::from_row_vals(rows, cols, &[], choice_constraints);
         let start = std::time::Instant::now();
         let Some(sol) = solver.solve_one_with(&made_choices) else {
                 println!("Found no solution!");
@@ -492,7 +503,7 @@ fn solve_file (fjob: FileSolveJob ,time_it: bool)
 
         let rows: &[Choice] = EMPTY_POSSIBLE_CHOICES.as_ref();
         let cols: &[Constraint] = EMPTY_CONSTRAINTS.as_ref();
-        let mut solver = dlx::UCSolver::from_pred(rows, cols, choice_satisies_constraint);
+        let mut solver = dlx::UCSolver::from_row_vals(rows, cols, &[], choice_constraints);
         let mut sols: Vec<SudokuBoard> = Vec::with_capacity(sudokus.len());
 
         // For each sudoku that we want to solve, we compute its representation
@@ -0,0 +1,197 @@
+use dancing_links_x::dlx;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+// Solves an exact cover problem given in the format of Knuth's DLX programs,
+// see dlx/knuth_format.rs, read from a file or from stdin.
+//
+// Usage: dlx [-a | -c | -u] [-n NODES] [-t] [FILE]
+//
+// By default the first solution is printed. With -a all solutions are printed,
+// with -c they are only counted, and -u tells whether there is exactly one.
+// A solution is printed as its options, one per line, followed by an empty line.
+// -n gives up after NODES row choices, and -t prints the time taken and
+// the number of row choices made to stderr, so the solutions stay easy to process.
+// The exit code is 0 if the search finished, 1 on bad input and 2 if it gave up.
+
+const USAGE: &str = "Usage: dlx [-a | -c | -u] [-n NODES] [-t] [FILE]";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+        First,
+        All,
+        Count,
+        Unique,
+}
+
+struct CLArguments {
+        mode:           Mode,
+        max_nodes:      Option<u64>,
+        time:           bool,
+
+        // Read from stdin if not given, or given as -.
+        path:           Option<String>,
+}
+
+fn parse_args () -> Result<CLArguments, String>
+{
+        let mut cla = CLArguments {mode: Mode::First, max_nodes: None, time: false, path: None};
+        let mut mode_given = false;
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+                let mode = match arg.as_str() {
+                        "-a" => Some (Mode::All),
+                        "-c" => Some (Mode::Count),
+                        "-u" => Some (Mode::Unique),
+                        _    => None,
+                };
+                if let Some(mode) = mode {
+                        if mode_given {
+                                return Err(String::from("Only one of -a, -c and -u can be given!"));
+                        }
+                        mode_given = true;
+                        cla.mode = mode;
+                        continue;
+                }
+                match arg.as_str() {
+                        "-t" => cla.time = true,
+                        "-n" => {
+                                let Some(nodes) = args.next() else {
+                                        return Err(String::from("Expected a number of nodes after -n!"));
+                                };
+                                let Ok(nodes) = nodes.parse() else {
+                                        return Err(format!("Can't use {nodes} as a number of nodes!"));
+                                };
+                                cla.max_nodes = Some (nodes);
+                        }
+                        "-h" | "--help" => return Err(String::from(USAGE)),
+                        _ if arg.starts_with('-') && arg != "-" => {
+                                return Err(format!("Unknown flag {arg}!\n{USAGE}"));
+                        }
+                        _ => {
+                                if cla.path.is_some() {
+                                        return Err(format!("More than one input given!\n{USAGE}"));
+                                }
+                                cla.path = Some (arg);
+                        }
+                }
+        }
+        Ok (cla)
+}
+
+fn read_input (path: Option<&str>) -> Result<String, String>
+{
+        match path {
+                None | Some ("-") => {
+                        let mut text = String::new();
+                        io::stdin().read_to_string(&mut text).map_err(|e| format!("Can't read stdin: {e}"))?;
+                        Ok (text)
+                }
+                Some (path) => std::fs::read_to_string(path).map_err(|e| format!("Can't read {path}: {e}")),
+        }
+}
+
+fn print_solution <W: Write> (out: &mut W, sol: &[String]) -> io::Result<()>
+{
+        for option in sol {
+                writeln!(out, "{option}")?;
+        }
+        writeln!(out)
+}
+
+// Runs the search, printing as it goes, and returns whether it finished.
+fn run (solver: &mut dlx::UCSolver<String>, mode: Mode, limits: dlx::Limits) -> io::Result<bool>
+{
+        let mut out = io::BufWriter::new(io::stdout().lock());
+        let finished = match mode {
+                Mode::Count => match solver.count_solutions_limited(None, &limits) {
+                        dlx::Outcome::Solved(cnt)       => {writeln!(out, "{cnt}")?; true}
+                        dlx::Outcome::Unsatisfiable     => {writeln!(out, "0")?; true}
+                        dlx::Outcome::Aborted           => false,
+                },
+                Mode::First | Mode::All => {
+                        let take = if mode == Mode::First {1} else {usize::MAX};
+                        let mut sols = solver.solutions_limited(limits);
+                        let mut cnt = 0;
+                        for sol in sols.by_ref().take(take) {
+                                print_solution(&mut out, &sol)?;
+                                cnt += 1;
+                        }
+                        let aborted = sols.aborted();
+                        if !aborted && cnt == 0 {
+                                writeln!(out, "no solution")?;
+                        }
+                        !aborted
+                }
+                Mode::Unique => {
+                        let mut sols = solver.solutions_limited(limits);
+                        let found: Vec<Vec<String>> = sols.by_ref().take(2).collect();
+                        // Two solutions settle it, even if the search gave up afterwards.
+                        match (found.len(), sols.aborted()) {
+                                (2, _)          => {
+                                        writeln!(out, "multiple")?;
+                                        print_solution(&mut out, &found[0])?;
+                                        print_solution(&mut out, &found[1])?;
+                                        true
+                                }
+                                (_, true)       => false,
+                                (0, false)      => {writeln!(out, "no solution")?; true}
+                                (_, false)      => {
+                                        writeln!(out, "unique")?;
+                                        print_solution(&mut out, &found[0])?;
+                                        true
+                                }
+                        }
+                }
+        };
+        out.flush()?;
+        Ok (finished)
+}
+
+fn main () -> ExitCode
+{
+        let cla = match parse_args() {
+                Ok (cla)  => cla,
+                Err (msg) => {
+                        eprintln!("{msg}");
+                        return ExitCode::from(1);
+                }
+        };
+        let text = match read_input(cla.path.as_deref()) {
+                Ok (text) => text,
+                Err (msg) => {
+                        eprintln!("{msg}");
+                        return ExitCode::from(1);
+                }
+        };
+        let mut solver = match dlx::UCSolver::from_dlx_format(&text) {
+                Ok (solver) => solver,
+                Err (err)   => {
+                        eprintln!("{err}");
+                        return ExitCode::from(1);
+                }
+        };
+
+        let mut limits = dlx::Limits::new();
+        if let Some(max_nodes) = cla.max_nodes {
+                limits = limits.with_max_nodes(max_nodes);
+        }
+        let start = std::time::Instant::now();
+        let finished = match run(&mut solver, cla.mode, limits) {
+                Ok (finished) => finished,
+                Err (err)     => {
+                        eprintln!("Can't write the solutions: {err}");
+                        return ExitCode::from(1);
+                }
+        };
+        let dur = start.elapsed();
+
+        if !finished {
+                eprintln!("Gave up after {} nodes", solver.stats().nodes);
+        }
+        if cla.time {
+                let s = dur.as_secs_f64();
+                eprintln!("That took {} ms and {} nodes", s * 1000.0, solver.stats().nodes);
+        }
+        if finished {ExitCode::SUCCESS} else {ExitCode::from(2)}
+}
@@ -1,16 +1,69 @@
 mod dancing_link_array_optional;
+mod column_chooser;
+mod row_order;
+mod rng;
+mod limits;
+mod stats;
+mod parallel;
+mod knuth_format;
+mod cnf;
+mod lp;
+mod error;
 
 use dancing_link_array_optional as dla;
+use stats::{Exclusive, Progress};
 use itertools::Itertools;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::num::{NonZeroU32, NonZeroU64};
+
+pub use column_chooser::{ColumnChooser, ColumnInfo, Leftmost, Mrv, MrvRandom, Priority};
+pub use row_order::{ByKey, RowInfo, RowOrder, Rows, Shuffle};
+pub use limits::{Limits, Outcome};
+pub use stats::SearchStats;
+pub use knuth_format::ParseError;
+pub use cnf::CnfEncoding;
+pub use error::DlxError;
+pub use dancing_link_array_optional::Step;
 
 // Public interface to the DLA.
 
+// Whether a column has to be covered, or may be covered.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub
+enum ColumnKind {
+        Strict,
+        Optional,
+}
+
+// What to do with a row that covers no column, see set_empty_rows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub
+enum EmptyRows {
+        // Fail with DlxError::EmptyRow.
+        #[default]
+        Reject,
+        // Keep the row, which is never part of a solution.
+        // Forcing it with set_state or a _with method changes nothing,
+        // and it can be deleted, but not be given a column with add_column.
+        Ignore,
+}
+
+// The color a row gives an optional column, see from_pred_colored.
+pub
+type Color = NonZeroU32;
+
+// Stands in for the node of a row kept under EmptyRows::Ignore, which has none.
+const EMPTY_ROW: dla::NodeIdx = dla::INVALID_NODE_IDX - 1;
+
 pub
 struct UCSolver <R: Clone + Eq> {
         array: dla::DancingLinkArray,
 
-        row_dat: Box<[R]>,
+        // Deleted rows keep their place, so that row indices stay valid.
+        row_dat: Vec<R>,
 
         // Keeps track of the state we put the array in since construction.
         // When removing a row from the DLA, we store a handle to it here,
@@ -18,21 +71,51 @@ struct UCSolver <R: Clone + Eq> {
         rm_rows: Vec<usize>,
 
         // We remember a single node for each row, for performance.
-        // This is constructed at construction and does not change.
-        to_rows: Box<[dla::NodeIdx]>
+        // This is constructed at construction, grows with add_row,
+        // and holds INVALID_NODE_IDX for deleted rows and EMPTY_ROW for ignored empty rows.
+        to_rows: Vec<dla::NodeIdx>,
+
+        // The names of the columns and colors, if the solver was read from Knuth's format.
+        // Used to write it back the same way.
+        names: Option<knuth_format::Names>,
+
+        // What add_row does with a row that covers no column.
+        empty_rows: EmptyRows,
+
+        // Gives rows added with add_row their cost, once with_row_costs was called.
+        row_cost: Option<RowCostFn<R>>,
+
+        // How often to call the progress callback, and the callback, see set_progress.
+        progress: Option<(NonZeroU64, Exclusive<ProgressFn<R>>)>,
 }
 
+type RowCostFn <R> = Box<dyn Fn(&R) -> u64 + Send + Sync>;
+type ProgressFn <R> = Box<dyn FnMut(usize, &[R]) + Send>;
+
 
 impl <R: Clone + Eq> UCSolver <R> {
 
         // The constructors:
+        // Each panics on bad input, such as an empty row,
+        // and has a try_ variant that returns a DlxError instead.
+        // Those try_ variants that can be given a row without columns also take
+        // what to do with it, which add_row goes on to do too, see set_empty_rows.
+        // from_it and the like only learn of a row through its columns, so never see an empty one.
+        // Columns that no row covers are fine: an empty strict column leaves no solution,
+        // and an empty optional column changes nothing.
 
         // Constructs a UCSolver by taking the rows, columns and
         // constructs a node there or not, depending on the given predicate.
         pub
         fn from_pred <C, P: Fn(&R, &C) -> bool> (rows: &[R], cols: &[C], p: P) -> UCSolver < R>
         {
-                let row_dat: Box<[R]> = rows.iter().cloned().collect();
+                or_panic(Self::try_from_pred(rows, cols, p, EmptyRows::Reject))
+        }
+
+        pub
+        fn try_from_pred <C, P: Fn(&R, &C) -> bool> (rows: &[R], cols: &[C], p: P, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        {
+                let row_dat: Vec<R> = rows.to_vec();
 
                 let idc_gen = rows.iter().enumerate()
                                 .cartesian_product(cols.iter().enumerate())
@@ -40,16 +123,20 @@ impl <R: Clone + Eq> UCSolver <R> {
                         .map (|((r_idx, _), (c_idx, _))| (r_idx, c_idx));
 
                 let dla = dla::DancingLinkArray::from_sorted_idc_unsafe(idc_gen, rows.len(), cols.len(), 0);
-                let to_rows = dla.to_each_row();
-
-                UCSolver {array: dla, row_dat, rm_rows: Vec::new(), to_rows}
+                Self::from_array(dla, row_dat).check_empty_rows(empty_rows)
         }
 
         // Like from_pred, but distinguishes strict and optional columns.
         pub
         fn from_pred_opt <C, P: Fn(&R, &C) -> bool> (rows: &[R], strict_cols: &[C], opt_cols: &[C], p: P) -> UCSolver < R>
         {
-                let row_dat: Box<[R]> = rows.iter().cloned().collect();
+                or_panic(Self::try_from_pred_opt(rows, strict_cols, opt_cols, p, EmptyRows::Reject))
+        }
+
+        pub
+        fn try_from_pred_opt <C, P: Fn(&R, &C) -> bool> (rows: &[R], strict_cols: &[C], opt_cols: &[C], p: P, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        {
+                let row_dat: Vec<R> = rows.to_vec();
 
                 let cols_it = strict_cols.iter().chain(opt_cols.iter());
 
@@ -62,18 +149,205 @@ impl <R: Clone + Eq> UCSolver <R> {
                 let num_opt_cols = opt_cols.len();
                 let dla = dla::DancingLinkArray::from_sorted_idc_unsafe(idc_gen, rows.len(), num_strict_cols, num_opt_cols);
 
-                let to_rows = dla.to_each_row();
+                Self::from_array(dla, row_dat).check_empty_rows(empty_rows)
+        }
+
+        // Like from_pred_opt, but strict column i has to be covered
+        // between bounds[i].0 and bounds[i].1 times, inclusive.
+        // An upper bound of 0 is not allowed.
+        pub
+        fn from_pred_bounded <C, P: Fn(&R, &C) -> bool> (rows: &[R], strict_cols: &[C], bounds: &[(u32, u32)], opt_cols: &[C], p: P) -> UCSolver < R>
+        {
+                or_panic(Self::try_from_pred_bounded(rows, strict_cols, bounds, opt_cols, p, EmptyRows::Reject))
+        }
+
+        pub
+        fn try_from_pred_bounded <C, P: Fn(&R, &C) -> bool> (rows: &[R], strict_cols: &[C], bounds: &[(u32, u32)], opt_cols: &[C], p: P, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        {
+                Self::try_from_pred_opt(rows, strict_cols, opt_cols, p, empty_rows)?.with_bounds(bounds)
+        }
+
+        // Like from_pred_opt, but rows may share an optional column,
+        // as long as they all give it the same color.
+        // The color function is consulted for each row in an optional column,
+        // and None means the row claims that column for itself, as in from_pred_opt.
+        pub
+        fn from_pred_colored <C, P, Q> (rows: &[R], strict_cols: &[C], opt_cols: &[C], p: P, color: Q) -> UCSolver < R>
+        where
+                P: Fn(&R, &C) -> bool,
+                Q: Fn(&R, &C) -> Option<Color>,
+        {
+                or_panic(Self::try_from_pred_colored(rows, strict_cols, opt_cols, p, color, EmptyRows::Reject))
+        }
+
+        pub
+        fn try_from_pred_colored <C, P, Q> (rows: &[R], strict_cols: &[C], opt_cols: &[C], p: P, color: Q, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        where
+                P: Fn(&R, &C) -> bool,
+                Q: Fn(&R, &C) -> Option<Color>,
+        {
+                let row_dat: Vec<R> = rows.to_vec();
+
+                let num_strict_cols = strict_cols.len();
+                let num_opt_cols = opt_cols.len();
+                let cols_it = strict_cols.iter().chain(opt_cols.iter());
+
+                let idc_gen = rows.iter().enumerate()
+                                .cartesian_product(cols_it.enumerate())
+                        .filter (|((_, r), (_, c))| p(r, c))
+                        .map (|((r_idx, r), (c_idx, c))| {
+                                let col_color = if c_idx < num_strict_cols {
+                                        None
+                                } else {
+                                        color(r, c)
+                                };
+                                (r_idx, c_idx, col_color.map_or(0, Color::get))
+                        });
+
+                let dla = dla::DancingLinkArray::from_sorted_colored_idc_unsafe(idc_gen, rows.len(), num_strict_cols, num_opt_cols);
+
+                Self::from_array(dla, row_dat).check_empty_rows(empty_rows)
+        }
+
+        // Constructs a UCSolver from the columns each row covers,
+        // which f gives as indices into cols, in any order.
+        // f is called once for each row, in order.
+        // Unlike from_pred, this takes time in the number of nodes, not rows times columns.
+        pub
+        fn from_row_fn <C, I, F> (rows: &[R], cols: &[C], f: F) -> UCSolver <R>
+        where
+                I: IntoIterator<Item = usize>,
+                F: FnMut(&R) -> I,
+        {
+                or_panic(Self::try_from_row_fn(rows, cols, f, EmptyRows::Reject))
+        }
+
+        pub
+        fn try_from_row_fn <C, I, F> (rows: &[R], cols: &[C], f: F, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        where
+                I: IntoIterator<Item = usize>,
+                F: FnMut(&R) -> I,
+        {
+                Self::try_from_row_fn_opt(rows, cols, &[], f, empty_rows)
+        }
+
+        // Like from_row_fn, but distinguishes strict and optional columns.
+        // Optional column i has index strict_cols.len() + i.
+        pub
+        fn from_row_fn_opt <C, I, F> (rows: &[R], strict_cols: &[C], opt_cols: &[C], f: F) -> UCSolver <R>
+        where
+                I: IntoIterator<Item = usize>,
+                F: FnMut(&R) -> I,
+        {
+                or_panic(Self::try_from_row_fn_opt(rows, strict_cols, opt_cols, f, EmptyRows::Reject))
+        }
+
+        pub
+        fn try_from_row_fn_opt <C, I, F> (rows: &[R], strict_cols: &[C], opt_cols: &[C], mut f: F, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        where
+                I: IntoIterator<Item = usize>,
+                F: FnMut(&R) -> I,
+        {
+                let row_dat: Vec<R> = rows.to_vec();
+                let num_cols = strict_cols.len() + opt_cols.len();
+
+                let mut idc: Vec<(usize, usize)> = Vec::new();
+                let mut row_cols: Vec<usize> = Vec::new();
+                for (r_idx, r) in rows.iter().enumerate() {
+                        row_cols.clear();
+                        row_cols.extend(f(r));
+                        row_cols.sort_unstable();
+                        if let Some(&c_idx) = row_cols.last().filter(|&&c_idx| c_idx >= num_cols) {
+                                return Err (DlxError::ColumnOutOfRange {row: r_idx, col: c_idx});
+                        }
+                        if let Some(w) = row_cols.windows(2).find(|w| w[0] == w[1]) {
+                                return Err (DlxError::DuplicateEntry {row: r_idx, col: w[0]});
+                        }
+                        idc.extend(row_cols.iter().map(|&c_idx| (r_idx, c_idx)));
+                }
+
+                let dla = dla::DancingLinkArray::from_sorted_idc_unsafe(idc, rows.len(), strict_cols.len(), opt_cols.len());
+                Self::from_array(dla, row_dat).check_empty_rows(empty_rows)
+        }
+
+        // Like from_row_fn_opt, but f gives the columns themselves,
+        // which are looked up in a hash table. No two columns may be equal.
+        pub
+        fn from_row_vals <C, I, F> (rows: &[R], strict_cols: &[C], opt_cols: &[C], f: F) -> UCSolver <R>
+        where
+                C: Hash + Eq,
+                I: IntoIterator<Item = C>,
+                F: FnMut(&R) -> I,
+        {
+                or_panic(Self::try_from_row_vals(rows, strict_cols, opt_cols, f, EmptyRows::Reject))
+        }
+
+        pub
+        fn try_from_row_vals <C, I, F> (rows: &[R], strict_cols: &[C], opt_cols: &[C], mut f: F, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        where
+                C: Hash + Eq,
+                I: IntoIterator<Item = C>,
+                F: FnMut(&R) -> I,
+        {
+                let mut lookup: HashMap<&C, usize> = HashMap::with_capacity(strict_cols.len() + opt_cols.len());
+                for (c_idx, c) in strict_cols.iter().chain(opt_cols).enumerate() {
+                        if lookup.insert(c, c_idx).is_some() {
+                                return Err (DlxError::DuplicateColumn {col: c_idx});
+                        }
+                }
+                let mut row_cols: Vec<Vec<usize>> = Vec::with_capacity(rows.len());
+                for (r_idx, r) in rows.iter().enumerate() {
+                        let cols: Option<Vec<usize>> = f(r).into_iter().map(|c| lookup.get(&c).copied()).collect();
+                        let Some(cols) = cols else {
+                                return Err (DlxError::UnknownColumn {row: r_idx});
+                        };
+                        row_cols.push(cols);
+                }
+                // The rows are given in order, so we can hand out their columns one by one.
+                let mut row_cols = row_cols.into_iter();
+                Self::try_from_row_fn_opt(rows, strict_cols, opt_cols, |_| row_cols.next().expect("One entry per row"), empty_rows)
+        }
 
-                UCSolver {array: dla, row_dat, rm_rows: Vec::new(), to_rows}
+        // Wraps a freshly constructed array, whose row i is row_dat[i].
+        fn from_array (array: dla::DancingLinkArray, row_dat: Vec<R>) -> UCSolver <R>
+        {
+                let to_rows = array.to_each_row();
+                UCSolver {array, row_dat, rm_rows: Vec::new(), to_rows, names: None, empty_rows: EmptyRows::Reject, row_cost: None, progress: None}
+        }
+
+        // Applies empty_rows to a freshly constructed solver, whose rows without columns
+        // have no node yet, and keeps it for add_row.
+        fn check_empty_rows (mut self, empty_rows: EmptyRows) -> Result<UCSolver <R>, DlxError>
+        {
+                let first_empty = self.to_rows.iter().position(|&node| node == dla::INVALID_NODE_IDX);
+                if let (EmptyRows::Reject, Some(row)) = (empty_rows, first_empty) {
+                        return Err (DlxError::EmptyRow {row});
+                }
+                // No row was deleted yet, so these are all empty.
+                for node in self.to_rows.iter_mut().filter(|node| **node == dla::INVALID_NODE_IDX) {
+                        *node = EMPTY_ROW;
+                }
+                self.empty_rows = empty_rows;
+                Ok (self)
         }
 
         // O(n^2).
         // Yeah, this could be faster for large n,
         // but it probably doesn't matter.
+        // If it does, see from_it_hashed.
         //
         // Constructs a UCSolver by taking all the nodes.
+        // Returns None if a row is given with the same column twice.
         pub
         fn from_it <C: Eq, I: IntoIterator<Item = (R, C)>> (it: I) -> Option<UCSolver <R>>
+        {
+                Self::try_from_it(it).ok()
+        }
+
+        // Like from_it, but says which row and column, numbered in order of appearance,
+        // are given together twice.
+        pub
+        fn try_from_it <C: Eq, I: IntoIterator<Item = (R, C)>> (it: I) -> Result<UCSolver <R>, DlxError>
         {
                 let abstract_idc: Box<[(R, C)]> = it.into_iter().collect();
                 let mut unique_rows:  Vec<R> = Vec::new();
@@ -101,61 +375,528 @@ impl <R: Clone + Eq> UCSolver <R> {
                         idc.push((row, col));
                 }
                 sort_idc_rowmaj (&mut idc);
-                if !sorted_idc_unique(&idc) {
-                        return None;
+                if let Some((row, col)) = sorted_idc_duplicate(&idc) {
+                        return Err (DlxError::DuplicateEntry {row, col});
                 }
 
                 let num_rows = unique_rows.len();
                 let num_cols = unique_cols.len();
                 let dla = dla::DancingLinkArray::from_sorted_idc_unsafe(idc, num_rows, num_cols, 0);
-                let to_rows = dla.to_each_row();
-                let rm_rows = Vec::new();
-                Some (UCSolver {array: dla, row_dat: unique_rows.into_boxed_slice(), rm_rows, to_rows})
+                Ok (Self::from_array(dla, unique_rows))
+        }
+
+        // Like from_it, but in O(n log n) rather than O(n^2): rows and columns are interned with hash tables,
+        // after which the pairs are sorted.
+        // kind tells which columns are strict and which optional.
+        // Rows keep the order in which they first appear, and so do columns,
+        // except that all strict columns come before the optional ones.
+        // If a row is given with the same column twice, that pair is returned as error.
+        pub
+        fn from_it_hashed <C, I, K> (it: I, kind: K) -> Result<UCSolver <R>, DuplicatePair<R, C>>
+        where
+                R: Hash,
+                C: Hash + Eq,
+                I: IntoIterator<Item = (R, C)>,
+                K: Fn(&C) -> ColumnKind,
+        {
+                let mut row_idc: HashMap<R, usize> = HashMap::new();
+                let mut col_idc: HashMap<C, usize> = HashMap::new();
+                let mut unique_rows: Vec<R> = Vec::new();
+                let mut idc: Vec<(usize, usize)> = Vec::new();
+
+                for (r, c) in it {
+                        let row = *row_idc.entry(r).or_insert_with_key(|r| {
+                                unique_rows.push(r.clone());
+                                unique_rows.len() - 1
+                        });
+                        let num_cols = col_idc.len();
+                        let col = *col_idc.entry(c).or_insert(num_cols);
+                        idc.push((row, col));
+                }
+
+                // Number the strict columns first, each kind in order of appearance.
+                let mut unique_cols: Vec<Option<C>> = Vec::new();
+                unique_cols.resize_with(col_idc.len(), || None);
+                for (c, col) in col_idc {
+                        unique_cols[col] = Some (c);
+                }
+                let unique_cols: Vec<C> = unique_cols.into_iter().map(|c| c.expect("Every index is used")).collect();
+                let kinds: Vec<ColumnKind> = unique_cols.iter().map(kind).collect();
+                let num_strict_cols = kinds.iter().filter(|&&k| k == ColumnKind::Strict).count();
+                let mut renumber: Vec<usize> = vec![0; unique_cols.len()];
+                let (mut next_strict, mut next_opt) = (0, num_strict_cols);
+                for (col, k) in kinds.iter().enumerate() {
+                        let next = if *k == ColumnKind::Strict {&mut next_strict} else {&mut next_opt};
+                        renumber[col] = *next;
+                        *next += 1;
+                }
+                idc.iter_mut().for_each(|(_, col)| *col = renumber[*col]);
+
+                sort_idc_rowmaj (&mut idc);
+                if let Some((row, col)) = sorted_idc_duplicate(&idc) {
+                        let col = renumber.iter().position(|&c| c == col).expect("Renumbering is a permutation");
+                        let mut unique_cols = unique_cols;
+                        return Err (DuplicatePair {
+                                row: unique_rows.swap_remove(row),
+                                col: unique_cols.swap_remove(col),
+                        });
+                }
+
+                let num_rows = unique_rows.len();
+                let num_opt_cols = unique_cols.len() - num_strict_cols;
+                let dla = dla::DancingLinkArray::from_sorted_idc_unsafe(idc, num_rows, num_strict_cols, num_opt_cols);
+                Ok (Self::from_array(dla, unique_rows))
+        }
+
+        // Gives every row a cost, for solve_min_cost.
+        // Meant to be chained onto a constructor. Rows added later are given theirs by cost too.
+        pub
+        fn with_row_costs <F: Fn(&R) -> u64 + Send + Sync + 'static> (mut self, cost: F) -> UCSolver <R>
+        {
+                let costs = self.row_dat.iter().map(&cost).collect();
+                self.array.set_row_costs(costs);
+                self.row_cost = Some (Box::new(cost));
+                self
+        }
+
+        // Sets how often each strict column has to be covered, as in from_pred_bounded,
+        // with bounds[i] for the i-th strict column in column order.
+        // Meant to be chained onto any constructor, so also onto the colored and sparse ones.
+        pub
+        fn with_bounds (mut self, bounds: &[(u32, u32)]) -> Result<UCSolver <R>, DlxError>
+        {
+                if !self.rm_rows.is_empty() {
+                        return Err (DlxError::RowsRemoved);
+                }
+                let num_strict_cols = (0..self.array.num_cols()).filter(|&col| self.array.is_strict(col)).count();
+                if bounds.len() != num_strict_cols {
+                        return Err (DlxError::WrongNumberOfBounds {expected: num_strict_cols, given: bounds.len()});
+                }
+                if let Some(col) = bounds.iter().position(|&(lo, hi)| lo > hi || hi == 0) {
+                        let (lo, hi) = bounds[col];
+                        return Err (DlxError::InvalidBounds {col, lo, hi});
+                }
+                self.array.set_bounds(bounds);
+                Ok (self)
         }
 
         // Returns a solution, if exists.
         pub
         fn solve_one (&mut self) -> Option<Vec<R>>
         {
-                let idc = self.array.solve_one()?;
-                Some (idc.into_iter().map(|idx| self.row_dat[idx as usize].clone()).collect())
+                self.solve_one_limited(&Limits::new()).unlimited()
         }
 
         // Returns all solutions.
         pub
         fn solve_many (&mut self) -> Vec<Vec<R>>
         {
-                let sols = self.array.solve_many();
-                sols.into_iter().map(|sol| {
-                        sol.into_iter().map(|idx| self.row_dat[idx as usize].clone()).collect()
-                }).collect()
+                self.solve_many_limited(&Limits::new()).unlimited().unwrap_or_default()
+        }
+
+        // Returns the number of solutions, without materialising them.
+        // With a cap, counting stops once that many are found,
+        // so e.g. a cap of 2 cheaply tells whether a solution is unique.
+        pub
+        fn count_solutions (&mut self, cap: Option<u128>) -> u128
+        {
+                self.count_solutions_limited(cap, &Limits::new()).unlimited().unwrap_or(0)
+        }
+
+        // Returns a solution with the lowest total row cost, and that cost.
+        // Without row costs, every row costs 1, so this finds a solution with the fewest rows.
+        // Parts of the search tree that cannot beat the best solution so far are skipped,
+        // using a lower bound from the cheapest rows of the columns left to cover.
+        // Ties go to the solution found first, so the result is deterministic.
+        // A total cost that does not fit in a u64 is taken to be u64::MAX.
+        pub
+        fn solve_min_cost (&mut self) -> Option<(Vec<R>, u64)>
+        {
+                self.solve_min_cost_limited(&Limits::new()).unlimited()
+        }
+
+        // Like solve_one, but gives up once one of the limits is exceeded.
+        // The solver is restored either way.
+        pub
+        fn solve_one_limited (&mut self, limits: &Limits) -> Outcome<Vec<R>>
+        {
+                self.with_progress(|array, progress| array.solve_one(limits, progress)).map(|idc| self.to_row_dat(idc))
+        }
+
+        // Like solve_many, but gives up once one of the limits is exceeded,
+        // in which case the solutions found so far are dropped.
+        pub
+        fn solve_many_limited (&mut self, limits: &Limits) -> Outcome<Vec<Vec<R>>>
+        {
+                self.with_progress(|array, progress| array.solve_many(limits, progress)).map(|sols| {
+                        sols.into_iter().map(|sol| self.to_row_dat(sol)).collect()
+                })
+        }
+
+        // Like solve_min_cost, but gives up once one of the limits is exceeded,
+        // in which case the best solution so far is dropped.
+        pub
+        fn solve_min_cost_limited (&mut self, limits: &Limits) -> Outcome<(Vec<R>, u64)>
+        {
+                self.with_progress(|array, progress| array.solve_min_cost(limits, progress)).map(|(idc, cost)| (self.to_row_dat(idc), cost))
+        }
+
+        // Like count_solutions, but gives up once one of the limits is exceeded.
+        pub
+        fn count_solutions_limited (&mut self, cap: Option<u128>, limits: &Limits) -> Outcome<u128>
+        {
+                self.with_progress(|array, progress| array.count_solutions(cap, limits, progress))
+        }
+
+        // The statistics of the last search, including searches through an
+        // iterator once it is dropped. Reset with every new search.
+        pub
+        fn stats (&self) -> &SearchStats
+        {
+                self.array.last_stats()
+        }
+
+        // Calls callback every so many row choices during every search, with
+        // the number of choices made so far and the rows chosen among them.
+        // Only rows that count towards the solution are given, see solve_one.
+        // Parallel searches do not call it.
+        pub
+        fn set_progress <F: FnMut(usize, &[R]) + Send + 'static> (&mut self, every: NonZeroU64, callback: F)
+        {
+                self.progress = Some ((every, Exclusive::new(Box::new(callback))));
+        }
+
+        // Stops calling the progress callback.
+        pub
+        fn clear_progress (&mut self)
+        {
+                self.progress = None;
+        }
+
+        // Runs a search on the array, with the progress callback if there is one.
+        // The row indices the search gives are mapped to the rows as they are then.
+        fn with_progress <T, F> (&mut self, search: F) -> T
+        where F: FnOnce(&mut dla::DancingLinkArray, Option<&mut Progress>) -> T
+        {
+                let UCSolver {array, row_dat, progress, ..} = self;
+                let Some((every, callback)) = progress else {
+                        return search(array, None);
+                };
+                let callback = callback.get_mut();
+                let mut rows = Vec::new();
+                let mut report = |depth: usize, idc: &[dla::NodeIdx]| {
+                        rows.clear();
+                        rows.extend(idc.iter().map(|&idx| row_dat[idx as usize].clone()));
+                        callback(depth, &rows);
+                };
+                search(array, Some (&mut Progress {every: every.get(), callback: &mut report}))
+        }
+
+        // Maps row indices back to the rows they came from.
+        fn to_row_dat (&self, idc: Vec<dla::NodeIdx>) -> Vec<R>
+        {
+                idc.into_iter().map(|idx| self.row_dat[idx as usize].clone()).collect()
+        }
+
+        // Like solve_many, but searches on all cores. The search tree is split
+        // at split_depth choices, and the subtrees below are searched in parallel.
+        // A larger split_depth gives more, smaller pieces of work.
+        // The other threads use the heuristics only if they can be forked,
+        // and do not report progress.
+        pub
+        fn par_solve_many (&mut self, split_depth: usize) -> Vec<Vec<R>>
+        {
+                let sols = parallel::solve_many(&mut self.array, split_depth);
+                sols.into_iter().map(|sol| self.to_row_dat(sol)).collect()
+        }
+
+        // Like count_solutions without a cap, but searches on all cores, see par_solve_many.
+        pub
+        fn par_count (&mut self, split_depth: usize) -> u128
+        {
+                parallel::count_solutions(&mut self.array, split_depth)
+        }
+
+        // Like solve_one, but searches on all cores, see par_solve_many.
+        // Returns whichever solution some thread finds first.
+        pub
+        fn par_solve_one (&mut self, split_depth: usize) -> Option<Vec<R>>
+        {
+                let sol = parallel::solve_one(&mut self.array, split_depth)?;
+                Some (self.to_row_dat(sol))
+        }
+
+        // Returns an iterator over all solutions, which are found one at a time.
+        // The solver is restored when the iterator is dropped.
+        pub
+        fn solutions (&mut self) -> Solutions<'_, R>
+        {
+                Solutions {solver: self, search: dla::Search::new(), saved: None, limits: None, aborted: false}
+        }
+
+        // Like solutions, but the iterator ends early once one of the limits is exceeded,
+        // counting all work since it was created. Solutions::aborted tells whether it did.
+        pub
+        fn solutions_limited (&mut self, limits: Limits) -> Solutions<'_, R>
+        {
+                Solutions {solver: self, search: dla::Search::new(), saved: None, limits: Some (limits), aborted: false}
+        }
+
+        // Returns a random solution, if one exists, by breaking column ties
+        // and ordering rows randomly. The same seed always gives the same solution.
+        // The configured heuristics are not used, and are left untouched.
+        pub
+        fn solve_random (&mut self, seed: u64) -> Option<Vec<R>>
+        {
+                self.solutions_random(seed).next()
+        }
+
+        // Like solutions, but in the random order of solve_random.
+        pub
+        fn solutions_random (&mut self, seed: u64) -> Solutions<'_, R>
+        {
+                let saved = self.array.set_heuristics(dla::Heuristics::random(seed));
+                Solutions {solver: self, search: dla::Search::new(), saved: Some(saved), limits: None, aborted: false}
+        }
+
+        // Sets the heuristic that picks which column to branch on.
+        // The default is Mrv.
+        pub
+        fn set_column_chooser <H: ColumnChooser + 'static> (&mut self, chooser: H)
+        {
+                let _ = self.array.set_chooser(Some(Box::new(chooser)));
+        }
+
+        // Sets the order in which the rows of the column we branch on are tried.
+        // The default is the order in which the rows were given.
+        pub
+        fn set_row_order <O: RowOrder + 'static> (&mut self, row_order: O)
+        {
+                let _ = self.array.set_row_order(Some(Box::new(row_order)));
+        }
+
+        // Tries rows with a lower key first, e.g. cheaper rows.
+        // The keys are computed once, here.
+        pub
+        fn set_row_order_by_key <K: Ord + Clone + Send + Sync + 'static, F: Fn(&R) -> K> (&mut self, key: F)
+        {
+                let keys: Box<[K]> = self.row_dat.iter().map(key).collect();
+                self.set_row_order(ByKey::new(keys));
+        }
+
+        // The index of the row with data r, if it exists and was not deleted.
+        fn find_row (&self, r: &R) -> Option<usize>
+        {
+                self.row_dat.iter().zip(&self.to_rows)
+                        .position(|(row, &node)| row == r && node != dla::INVALID_NODE_IDX)
+        }
+
+        // A node of row r_idx, unless it was deleted or is an ignored empty row.
+        fn row_node (&self, r_idx: usize) -> Option<dla::NodeIdx>
+        {
+                Some (self.to_rows[r_idx]).filter(|&node| node != dla::INVALID_NODE_IDX && node != EMPTY_ROW)
+        }
+
+        // Adds a row that has the given columns, numbered as in from_pred_opt:
+        // strict columns first, then optional ones, then those added with add_column.
+        // Can only be done while no rows are removed with set_state.
+        // Rows are identified by their data, so it must not equal that of another row.
+        pub
+        fn add_row (&mut self, data: R, cols: &[usize])
+        {
+                or_panic(self.try_add_row(data, cols))
+        }
+
+        pub
+        fn try_add_row (&mut self, data: R, cols: &[usize]) -> Result<(), DlxError>
+        {
+                let cols: Vec<(usize, Option<Color>)> = cols.iter().map(|&col| (col, None)).collect();
+                self.try_add_colored_row(data, &cols)
+        }
+
+        // Like add_row, but each column comes with the color the row gives it, as in from_pred_colored.
+        // Only optional columns can be given a color.
+        pub
+        fn add_colored_row (&mut self, data: R, cols: &[(usize, Option<Color>)])
+        {
+                or_panic(self.try_add_colored_row(data, cols))
+        }
+
+        pub
+        fn try_add_colored_row (&mut self, data: R, cols: &[(usize, Option<Color>)]) -> Result<(), DlxError>
+        {
+                if !self.rm_rows.is_empty() {
+                        return Err (DlxError::RowsRemoved);
+                }
+                if self.find_row(&data).is_some() {
+                        return Err (DlxError::RowExists);
+                }
+                let row = self.row_dat.len();
+                let mut sorted: Vec<usize> = cols.iter().map(|&(col, _)| col).collect();
+                sorted.sort_unstable();
+                if sorted.is_empty() {
+                        if self.empty_rows == EmptyRows::Reject {
+                                return Err (DlxError::EmptyRow {row});
+                        }
+                        self.array.add_empty_row();
+                        self.push_row(data, EMPTY_ROW);
+                        return Ok (());
+                }
+                if let Some(&col) = sorted.last().filter(|&&col| col >= self.array.num_cols()) {
+                        return Err (DlxError::ColumnOutOfRange {row, col});
+                }
+                if let Some(w) = sorted.windows(2).find(|w| w[0] == w[1]) {
+                        return Err (DlxError::DuplicateEntry {row, col: w[0]});
+                }
+                if let Some(&(col, _)) = cols.iter().find(|&&(col, color)| color.is_some() && self.array.is_strict(col)) {
+                        return Err (DlxError::ColoredStrictColumn {row, col});
+                }
+                let cols: Vec<(usize, u32)> = cols.iter().map(|&(col, color)| (col, color.map_or(0, Color::get))).collect();
+                if let Some(names) = &mut self.names {
+                        knuth_format::name_colors(names, cols.iter().map(|&(_, color)| color));
+                }
+                let node = self.array.add_row(&cols);
+                self.push_row(data, node);
+                Ok (())
+        }
+
+        // Keeps the data of a row just added to the array, with its node, and gives it its cost.
+        fn push_row (&mut self, data: R, node: dla::NodeIdx)
+        {
+                if let Some(cost) = &self.row_cost {
+                        self.array.set_row_cost(self.row_dat.len(), cost(&data));
+                }
+                self.row_dat.push(data);
+                self.to_rows.push(node);
+        }
+
+        // Whether add_row fails on a row that covers no column, the default,
+        // or keeps it as if it was deleted right away.
+        // Starts out as given to the try_ constructor, if it took one.
+        pub
+        fn set_empty_rows (&mut self, empty_rows: EmptyRows)
+        {
+                self.empty_rows = empty_rows;
+        }
+
+        // Adds a column, which the given rows get a node in, and returns its index.
+        // It comes after all existing columns, so add_row refers to it by that index.
+        // A strict column has to be covered exactly once.
+        // Can only be done while no rows are removed with set_state.
+        pub
+        fn add_column (&mut self, kind: ColumnKind, members: &[R]) -> usize
+        {
+                or_panic(self.try_add_column(kind, members))
+        }
+
+        pub
+        fn try_add_column (&mut self, kind: ColumnKind, members: &[R]) -> Result<usize, DlxError>
+        {
+                if !self.rm_rows.is_empty() {
+                        return Err (DlxError::RowsRemoved);
+                }
+                let mut nodes: Vec<dla::NodeIdx> = Vec::with_capacity(members.len());
+                for r in members {
+                        let Some(r_idx) = self.find_row(r) else {
+                                return Err (DlxError::UnknownRow);
+                        };
+                        let Some(node) = self.row_node(r_idx) else {
+                                return Err (DlxError::EmptyRowInColumn {row: r_idx});
+                        };
+                        nodes.push(node);
+                }
+                let col = self.array.add_column(kind == ColumnKind::Strict, &nodes);
+                if let Some(names) = &mut self.names {
+                        let name = knuth_format::fresh_item_name(names, col);
+                        names.push_item(name);
+                }
+                Ok (col)
+        }
+
+        // Deletes a row for good.
+        // Can only be done while no rows are removed with set_state.
+        pub
+        fn delete_row (&mut self, r: &R)
+        {
+                or_panic(self.try_delete_row(r))
+        }
+
+        pub
+        fn try_delete_row (&mut self, r: &R) -> Result<(), DlxError>
+        {
+                if !self.rm_rows.is_empty() {
+                        return Err (DlxError::RowsRemoved);
+                }
+                let Some(r_idx) = self.find_row(r) else {
+                        return Err (DlxError::UnknownRow);
+                };
+                if let Some(node) = self.row_node(r_idx) {
+                        self.array.delete_row(node);
+                }
+                self.to_rows[r_idx] = dla::INVALID_NODE_IDX;
+                Ok (())
+        }
+
+        // Whether row r_idx can not be in a solution together with the removed rows,
+        // because they would cover a column too often, or give it different colors.
+        // Ignored empty rows cover nothing, so never conflict.
+        fn conflicts_with_removed (&self, r_idx: usize) -> bool
+        {
+                let Some(node) = self.row_node(r_idx) else {
+                        return false;
+                };
+                let cols = self.array.row_cols(node);
+                let mut uses: Vec<u32> = vec![0; cols.len()];
+                for removed in self.rm_rows.iter().filter_map(|&removed| self.row_node(removed)) {
+                        for (col, color) in self.array.row_cols(removed) {
+                                let Ok(i) = cols.binary_search_by_key(&col, |&(c, _)| c) else {
+                                        continue;
+                                };
+                                if !self.array.is_strict(col) && (color == 0 || color != cols[i].1) {
+                                        return true;
+                                }
+                                uses[i] += 1;
+                        }
+                }
+                cols.iter().zip(&uses).any(|(&(col, _), &used)| {
+                        self.array.is_strict(col) && used + 1 > self.array.col_bounds(col).1
+                })
         }
 
         // Applies one change to the board.
         // Also sets internal state.
-        fn set_state1 (&mut self, r: &R)
+        fn try_set_state1 (&mut self, r: &R) -> Result<(), DlxError>
         {
                 // First we find the row-index corresponding to this particular given row.
-                let opt_r_idx = self.row_dat.iter().position(|row| row == r);
+                let opt_r_idx = self.find_row(r);
 
                 // Obviously, this row has to exist.
                 let Some(r_idx) = opt_r_idx else {
-                        panic! ("Tried to remove a non-existant row!");
+                        return Err (DlxError::UnknownRow);
                 };
 
-                // And this row has to be currently not removed.
+                // And this row has to be currently not removed, and fit with those that are.
                 if self.rm_rows.contains(&r_idx) {
-                        panic!("Tried to remove an already removed row!");
+                        return Err (DlxError::RowAlreadyRemoved);
+                }
+                if self.conflicts_with_removed(r_idx) {
+                        return Err (DlxError::RowConflicts);
                 }
 
                 // We find some node in the dla that has this row, and remove it.
-                // We also remember we removed this row.
+                // We also remember we removed this row, even an ignored empty one, which has no node.
 
-                let node_entry: dla::NodeIdx = self.to_rows[r_idx];
-                self.array.rm_row(node_entry);
+                if let Some(node_entry) = self.row_node(r_idx) {
+                        self.array.rm_row(node_entry);
+                }
                 self.rm_rows.push(r_idx);
+                Ok (())
         }
 
+        fn set_state1 (&mut self, r: &R)
+        {
+                or_panic(self.try_set_state1(r))
+        }
 
         pub
         fn set_state <'b, I: IntoIterator<Item = &'b R>>(&mut self, r_it: I)
@@ -166,14 +907,56 @@ impl <R: Clone + Eq> UCSolver <R> {
                 }
         }
 
+        // Like set_state, but fails on a row that does not exist, is already removed,
+        // or conflicts with the removed rows, in which case no row is removed at all.
+        // Returns the number of rows removed, to pass to recover_n.
+        pub
+        fn try_set_state <'b, I: IntoIterator<Item = &'b R>>(&mut self, r_it: I) -> Result<usize, DlxError>
+        where R: 'b
+        {
+                let mut cnt = 0;
+                for r in r_it {
+                        if let Err(err) = self.try_set_state1(r) {
+                                self.recover_n(cnt);
+                                return Err (err);
+                        }
+                        cnt += 1;
+                }
+                Ok (cnt)
+        }
+
         // Recovers n changes, previously made with set_state.
         pub
         fn recover_n (&mut self, n: usize)
         {
+                or_panic(self.try_recover_n(n))
+        }
+
+        // Like recover_n, but fails without recovering anything if fewer than n changes were made.
+        pub
+        fn try_recover_n (&mut self, n: usize) -> Result<(), DlxError>
+        {
+                if n > self.rm_rows.len() {
+                        return Err (DlxError::NothingToRecover {requested: n, removed: self.rm_rows.len()});
+                }
                 for _ in 0..n {
-                        let r_idx = self.rm_rows.pop().expect("Tried to recover nonexistent change!");
-                        let entry_node = self.to_rows[r_idx];
-                        self.array.insert_row(entry_node);
+                        let r_idx = self.rm_rows.pop().expect("Checked above");
+                        if let Some(entry_node) = self.row_node(r_idx) {
+                                self.array.insert_row(entry_node);
+                        }
+                }
+                Ok (())
+        }
+
+        // try_set_state for the _with methods, which have no solution, so give None,
+        // when the forced rows conflict with each other.
+        fn forced_state <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I) -> Result<Option<usize>, DlxError>
+        where R: 'b
+        {
+                match self.try_set_state(r_it) {
+                        Ok (cnt)                      => Ok (Some (cnt)),
+                        Err (DlxError::RowConflicts)  => Ok (None),
+                        Err (err)                     => Err (err),
                 }
         }
 
@@ -182,14 +965,42 @@ impl <R: Clone + Eq> UCSolver <R> {
         fn solve_one_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I) -> Option<Vec<R>>
         where R: 'b
         {
-                let mut cnt = 0;
-                for r in r_it {
-                        self.set_state1(r);
-                        cnt += 1;
-                }
+                or_panic(self.try_solve_one_with(r_it))
+        }
+
+        // Like solve_one_with, but fails on a forced row that does not exist or is given twice.
+        // The try_ variants of the other _with methods do the same.
+        pub
+        fn try_solve_one_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I) -> Result<Option<Vec<R>>, DlxError>
+        where R: 'b
+        {
+                let Some(cnt) = self.forced_state(r_it)? else {
+                        return Ok (None);
+                };
                 let sol = self.solve_one();
                 self.recover_n(cnt);
-                sol
+                Ok (sol)
+        }
+
+        // Like solve_one_with, but gives up once one of the limits is exceeded, as solve_one_limited.
+        // Forced rows that conflict with each other leave nothing to solve, so give Unsatisfiable.
+        pub
+        fn solve_one_with_limited <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, limits: &Limits) -> Outcome<Vec<R>>
+        where R: 'b
+        {
+                or_panic(self.try_solve_one_with_limited(r_it, limits))
+        }
+
+        pub
+        fn try_solve_one_with_limited <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, limits: &Limits) -> Result<Outcome<Vec<R>>, DlxError>
+        where R: 'b
+        {
+                let Some(cnt) = self.forced_state(r_it)? else {
+                        return Ok (Outcome::Unsatisfiable);
+                };
+                let sol = self.solve_one_limited(limits);
+                self.recover_n(cnt);
+                Ok (sol)
         }
 
         // Wrapper around set_state >> solve_many >> recover_n.
@@ -197,14 +1008,243 @@ impl <R: Clone + Eq> UCSolver <R> {
         fn solve_many_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I) -> Vec<Vec<R>>
         where R: 'b
         {
-                let mut cnt = 0;
-                for r in r_it {
-                        self.set_state1(r);
-                        cnt += 1;
-                }
+                or_panic(self.try_solve_many_with(r_it))
+        }
+
+        pub
+        fn try_solve_many_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I) -> Result<Vec<Vec<R>>, DlxError>
+        where R: 'b
+        {
+                let Some(cnt) = self.forced_state(r_it)? else {
+                        return Ok (Vec::new());
+                };
                 let sol = self.solve_many();
                 self.recover_n(cnt);
-                sol
+                Ok (sol)
+        }
+
+        // Like solve_many_with, but gives up once one of the limits is exceeded, as solve_many_limited.
+        pub
+        fn solve_many_with_limited <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, limits: &Limits) -> Outcome<Vec<Vec<R>>>
+        where R: 'b
+        {
+                or_panic(self.try_solve_many_with_limited(r_it, limits))
+        }
+
+        pub
+        fn try_solve_many_with_limited <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, limits: &Limits) -> Result<Outcome<Vec<Vec<R>>>, DlxError>
+        where R: 'b
+        {
+                let Some(cnt) = self.forced_state(r_it)? else {
+                        return Ok (Outcome::Unsatisfiable);
+                };
+                let sols = self.solve_many_limited(limits);
+                self.recover_n(cnt);
+                Ok (sols)
+        }
+
+        // Tells whether there is no solution, exactly one, or more,
+        // stopping as soon as a second solution is found.
+        pub
+        fn uniqueness (&mut self) -> Uniqueness<R>
+        {
+                let mut sols = self.solutions();
+                let Some(first) = sols.next() else {
+                        return Uniqueness::None;
+                };
+                match sols.next() {
+                        Some (second) => Uniqueness::Multiple(first, second),
+                        None          => Uniqueness::Unique(first),
+                }
+        }
+
+        // Wrapper around set_state >> uniqueness >> recover_n.
+        // The forced rows are not part of the returned solutions.
+        pub
+        fn uniqueness_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I) -> Uniqueness<R>
+        where R: 'b
+        {
+                or_panic(self.try_uniqueness_with(r_it))
+        }
+
+        pub
+        fn try_uniqueness_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I) -> Result<Uniqueness<R>, DlxError>
+        where R: 'b
+        {
+                let Some(cnt) = self.forced_state(r_it)? else {
+                        return Ok (Uniqueness::None);
+                };
+                let uniq = self.uniqueness();
+                self.recover_n(cnt);
+                Ok (uniq)
+        }
+
+        // Wrapper around set_state >> count_solutions >> recover_n.
+        pub
+        fn count_solutions_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, cap: Option<u128>) -> u128
+        where R: 'b
+        {
+                or_panic(self.try_count_solutions_with(r_it, cap))
+        }
+
+        pub
+        fn try_count_solutions_with <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, cap: Option<u128>) -> Result<u128, DlxError>
+        where R: 'b
+        {
+                let Some(cnt) = self.forced_state(r_it)? else {
+                        return Ok (0);
+                };
+                let num_sols = self.count_solutions(cap);
+                self.recover_n(cnt);
+                Ok (num_sols)
+        }
+
+        // Like count_solutions_with, but gives up once one of the limits is exceeded, as count_solutions_limited.
+        pub
+        fn count_solutions_with_limited <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, cap: Option<u128>, limits: &Limits) -> Outcome<u128>
+        where R: 'b
+        {
+                or_panic(self.try_count_solutions_with_limited(r_it, cap, limits))
+        }
+
+        pub
+        fn try_count_solutions_with_limited <'b, I: IntoIterator<Item = &'b R>> (&mut self, r_it: I, cap: Option<u128>, limits: &Limits) -> Result<Outcome<u128>, DlxError>
+        where R: 'b
+        {
+                let Some(cnt) = self.forced_state(r_it)? else {
+                        return Ok (Outcome::Unsatisfiable);
+                };
+                let num_sols = self.count_solutions_limited(cap, limits);
+                self.recover_n(cnt);
+                Ok (num_sols)
+        }
+}
+
+// A row that was given with the same column more than once, see from_it_hashed.
+// Unlike DlxError::DuplicateEntry, this holds the row and column themselves, not their indices.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub
+struct DuplicatePair <R, C> {
+        pub row:        R,
+        pub col:        C,
+}
+
+impl <R: fmt::Debug, C: fmt::Debug> fmt::Display for DuplicatePair <R, C> {
+        fn fmt (&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+        {
+                write!(f, "Row {:?} is given with column {:?} more than once", self.row, self.col)
+        }
+}
+
+impl <R: fmt::Debug, C: fmt::Debug> std::error::Error for DuplicatePair <R, C> {}
+
+// Whether a problem has no solution, exactly one, or more than one.
+// The solutions found are given as witnesses.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub
+enum Uniqueness <R> {
+        None,
+        Unique (Vec<R>),
+        Multiple (Vec<R>, Vec<R>),
+}
+
+// Lazy iterator over the solutions of a UCSolver.
+// The search is suspended between calls to next,
+// and holds on to the solver until it is dropped.
+pub
+struct Solutions <'a, R: Clone + Eq> {
+        solver: &'a mut UCSolver<R>,
+        search: dla::Search,
+
+        // The heuristics of the solver, if we replaced them for this search.
+        saved: Option<dla::Heuristics>,
+
+        limits: Option<Limits>,
+        aborted: bool,
+}
+
+impl <R: Clone + Eq> Solutions <'_, R> {
+        // What the search did so far.
+        pub
+        fn stats (&self) -> &SearchStats
+        {
+                self.search.stats()
+        }
+
+        // Whether the iterator ended because a limit was exceeded,
+        // rather than because there are no more solutions.
+        pub
+        fn aborted (&self) -> bool
+        {
+                self.aborted
+        }
+
+        // Does a single unit of work, choosing a row or taking one back,
+        // so that the search can be paused anywhere and not only at solutions.
+        // Can be mixed freely with next. A limit that is exceeded gives Exhausted, as next gives None.
+        pub
+        fn step (&mut self) -> Step
+        {
+                if self.aborted {
+                        return Step::Exhausted;
+                }
+                if self.limits.as_ref().is_some_and(|limits| limits.exceeded(true)) {
+                        self.aborted = true;
+                        return Step::Exhausted;
+                }
+                let max_nodes = self.limits.as_ref().and_then(|limits| limits.max_nodes);
+                let search = &mut self.search;
+                let step = self.solver.with_progress(|array, progress| search.step_budgeted(array, max_nodes, progress));
+                step.unwrap_or_else(|| {
+                        self.aborted = true;
+                        Step::Exhausted
+                })
+        }
+
+        // The rows chosen so far, in the order they were chosen.
+        // Right after step gave Solution, or next gave Some, these form that solution.
+        pub
+        fn partial (&self) -> Vec<R>
+        {
+                let row_dat = &self.solver.row_dat;
+                self.search.chosen_rows(&self.solver.array).map(|idx| row_dat[idx as usize].clone()).collect()
+        }
+}
+
+impl <R: Clone + Eq> Iterator for Solutions <'_, R> {
+        type Item = Vec<R>;
+
+        fn next (&mut self) -> Option<Vec<R>>
+        {
+                if self.aborted {
+                        return None;
+                }
+                let no_limits = Limits::new();
+                let limits = self.limits.as_ref().unwrap_or(&no_limits);
+                let search = &mut self.search;
+                let found = self.solver.with_progress(|array, progress| search.next_solution_limited(array, limits, progress));
+                let Some(found) = found else {
+                        self.aborted = true;
+                        return None;
+                };
+                if !found {
+                        return None;
+                }
+                Some (self.partial())
+        }
+}
+
+impl <R: Clone + Eq> Drop for Solutions <'_, R> {
+        fn drop (&mut self)
+        {
+                // The search may have been abandoned halfway,
+                // so we put back whatever rows it still has removed.
+                self.search.reset(&mut self.solver.array);
+                let stats = self.search.take_stats();
+                self.solver.array.set_last_stats(stats);
+                if let Some(saved) = self.saved.take() {
+                        self.solver.array.set_heuristics(saved);
+                }
         }
 }
 
@@ -223,19 +1263,14 @@ fn sort_idc_rowmaj (idc: &mut [(usize, usize)])
         idc.sort_unstable_by(ord);
 }
 
-// Takes sorted elements, and returns wether all elements are unique.
-fn sorted_idc_unique (idc: &[(usize, usize)]) -> bool
+// Unwraps the result of a try_ method, for the method that panics instead.
+fn or_panic <T> (res: Result<T, DlxError>) -> T
 {
-        let mut it = idc.iter();
-        let Some(&(mut r, mut c)) = it.next() else {
-                return true;
-        };
-        for (r_, c_) in it {
-                if (*r_ == r) && (*c_ == c) {
-                        return false;
-                }
-                r = *r_;
-                c = *c_;
-        }
-        true
+        res.unwrap_or_else(|err| panic!("{err}"))
+}
+
+// Takes sorted elements, and returns the first element that is given twice, if any.
+fn sorted_idc_duplicate (idc: &[(usize, usize)]) -> Option<(usize, usize)>
+{
+        idc.windows(2).find(|w| w[0] == w[1]).map(|w| w[0])
 }
@@ -0,0 +1,256 @@
+// Writing instances as a SAT problem in DIMACS CNF, to cross-check solutions
+// and compare with SAT solvers.
+//
+// Every row becomes a variable, which is true when the row is chosen.
+// A strict column gets an exactly-one constraint over its rows and an optional column
+// an at-most-one constraint, written with the selected encoding.
+// Strict columns with other bounds always get a sequential counter.
+// In a colored optional column, all rows with the same color share one extra variable,
+// and at most one of those and the uncolored rows may be true.
+
+use super::UCSolver;
+use std::io::{self, Write};
+
+// How at-most-one constraints are written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub
+enum CnfEncoding {
+        // A clause for every pair of rows. No extra variables, but quadratic in size.
+        Pairwise,
+        // Sinz's sequential counter, with n - 1 extra variables and about 3n clauses.
+        Sequential,
+        // Klieber and Kwon's commander encoding, with groups of three.
+        Commander,
+}
+
+// Literals are DIMACS integers: a variable, or its negation.
+type Lit = i64;
+
+struct Cnf {
+        num_vars:       i64,
+        clauses:        Vec<Vec<Lit>>,
+}
+
+impl Cnf {
+        fn new_var (&mut self) -> Lit
+        {
+                self.num_vars += 1;
+                self.num_vars
+        }
+
+        fn add (&mut self, clause: Vec<Lit>)
+        {
+                self.clauses.push(clause);
+        }
+
+        fn at_most_one (&mut self, lits: &[Lit], encoding: CnfEncoding)
+        {
+                // Size of the groups of the commander encoding.
+                const GROUP: usize = 3;
+
+                if lits.len() <= 1 {
+                        return;
+                }
+                match encoding {
+                        CnfEncoding::Pairwise => {
+                                for (i, &a) in lits.iter().enumerate() {
+                                        for &b in &lits[i + 1..] {
+                                                self.add(vec![-a, -b]);
+                                        }
+                                }
+                        }
+                        CnfEncoding::Sequential => {
+                                // s_i tells that one of the first i + 1 literals is true.
+                                let n = lits.len();
+                                let mut prev = self.new_var();
+                                self.add(vec![-lits[0], prev]);
+                                for &x in &lits[1..n - 1] {
+                                        let s = self.new_var();
+                                        self.add(vec![-x, s]);
+                                        self.add(vec![-prev, s]);
+                                        self.add(vec![-x, -prev]);
+                                        prev = s;
+                                }
+                                self.add(vec![-lits[n - 1], -prev]);
+                        }
+                        CnfEncoding::Commander => {
+                                if lits.len() <= GROUP + 1 {
+                                        self.at_most_one(lits, CnfEncoding::Pairwise);
+                                        return;
+                                }
+                                // Each group has a commander that is true exactly when one of the group is.
+                                let mut commanders = Vec::new();
+                                for group in lits.chunks(GROUP) {
+                                        self.at_most_one(group, CnfEncoding::Pairwise);
+                                        let c = self.new_var();
+                                        let mut clause = vec![-c];
+                                        clause.extend_from_slice(group);
+                                        self.add(clause);
+                                        for &x in group {
+                                                self.add(vec![-x, c]);
+                                        }
+                                        commanders.push(c);
+                                }
+                                self.at_most_one(&commanders, CnfEncoding::Commander);
+                        }
+                }
+        }
+
+        // Between lo and hi of the literals are true, with a sequential counter
+        // whose variable r_j tells that at least j of the literals so far are true.
+        fn cardinality (&mut self, lits: &[Lit], lo: u32, hi: u32)
+        {
+                let (lo, hi) = (lo as usize, hi as usize);
+                if lo > lits.len() {
+                        self.add(Vec::new());
+                        return;
+                }
+                if lo == 0 && hi >= lits.len() {
+                        return;
+                }
+                // We need to count up to hi + 1 to rule it out, and up to lo to require it.
+                let top = lits.len().min(lo.max(hi + 1));
+                // None is false, and the count of 0 is always reached.
+                let mut prev: Vec<Option<Lit>> = vec![None; top + 1];
+                for &x in lits {
+                        let mut cur: Vec<Option<Lit>> = vec![None; top + 1];
+                        for j in 1..=top {
+                                // r_j = a or (x and b), with a the old r_j and b the old r_(j - 1).
+                                let a = prev[j];
+                                let b = if j == 1 {None} else {prev[j - 1]};
+                                if j > 1 && b.is_none() {
+                                        // We can not have counted j - 1 yet, so r_j stays as it was.
+                                        cur[j] = a;
+                                        continue;
+                                }
+                                let Some(a) = a else {
+                                        if j == 1 {
+                                                cur[j] = Some (x);
+                                        } else {
+                                                let (r, b) = (self.new_var(), b.unwrap());
+                                                self.add(vec![-x, -b, r]);
+                                                self.add(vec![-r, x]);
+                                                self.add(vec![-r, b]);
+                                                cur[j] = Some (r);
+                                        }
+                                        continue;
+                                };
+                                let r = self.new_var();
+                                self.add(vec![-a, r]);
+                                self.add(vec![-r, a, x]);
+                                match b {
+                                        None    => self.add(vec![-x, r]),
+                                        Some(b) => {
+                                                self.add(vec![-x, -b, r]);
+                                                self.add(vec![-r, a, b]);
+                                        }
+                                }
+                                cur[j] = Some (r);
+                        }
+                        prev = cur;
+                }
+                if lo > 0 {
+                        self.add(vec![prev[lo].expect("Enough literals to count to lo")]);
+                }
+                if let Some(&Some(r)) = prev.get(hi + 1) {
+                        self.add(vec![-r]);
+                }
+        }
+}
+
+// Gives the variables of a column that at most one of may be set, for the exports.
+// Uncolored rows count themselves, and rows of the same color stand together behind one shared variable,
+// made by new_shared the first time the color comes up. imply then has each row set the variable of its color.
+pub(super)
+fn exclusive_vars <T, V: Copy> (target: &mut T, members: &[(V, u32)], new_shared: impl Fn(&mut T, u32) -> V, imply: impl Fn(&mut T, V, V)) -> Vec<V>
+{
+        let mut exclusive: Vec<V> = Vec::new();
+        let mut colors: Vec<(u32, V)> = Vec::new();
+        for &(var, color) in members {
+                if color == 0 {
+                        exclusive.push(var);
+                        continue;
+                }
+                let shared = match colors.iter().find(|&&(c, _)| c == color) {
+                        Some (&(_, shared)) => shared,
+                        None                => {
+                                let shared = new_shared(target, color);
+                                colors.push((color, shared));
+                                exclusive.push(shared);
+                                shared
+                        }
+                };
+                imply(target, var, shared);
+        }
+        exclusive
+}
+
+impl <R: Clone + Eq> UCSolver <R> {
+        // The rows as write_dlx_format writes them, each with the variable new_var gives it,
+        // and the variables of each column, with the color the row gives it.
+        // new_var is also told whether the row has a strict column, as otherwise the search never chooses it.
+        pub(super)
+        fn column_members <V: Copy> (&self, mut new_var: impl FnMut(&R, bool) -> V) -> (Vec<R>, Vec<Vec<(V, u32)>>)
+        {
+                let mut rows: Vec<R> = Vec::new();
+                let mut members: Vec<Vec<(V, u32)>> = vec![Vec::new(); self.array.num_cols()];
+                for r_idx in 0..self.to_rows.len() {
+                        let Some(node) = self.row_node(r_idx) else {
+                                continue;
+                        };
+                        let r = &self.row_dat[r_idx];
+                        let cols = self.array.row_cols(node);
+                        let var = new_var(r, cols.iter().any(|&(col, _)| self.array.is_strict(col)));
+                        rows.push(r.clone());
+                        for (col, color) in cols {
+                                members[col].push((var, color));
+                        }
+                }
+                (rows, members)
+        }
+
+        // Writes the solver in DIMACS CNF, see the top of this file,
+        // with the same rows as write_dlx_format.
+        // The search never chooses a row without any strict column, so its variable is made false.
+        // Returns the rows in the order of their variables: variable v is row v - 1 of the result.
+        // The variables after those are only used by the encoding.
+        pub
+        fn to_cnf <W: Write> (&self, out: &mut W, encoding: CnfEncoding) -> io::Result<Vec<R>>
+        {
+                let mut cnf = Cnf {num_vars: 0, clauses: Vec::new()};
+                let (rows, members) = self.column_members(|_, choosable| {
+                        let var = cnf.new_var();
+                        if !choosable {
+                                cnf.add(vec![-var]);
+                        }
+                        var
+                });
+
+                for (col, members) in members.iter().enumerate() {
+                        let lits: Vec<Lit> = members.iter().map(|&(var, _)| var).collect();
+                        if self.array.is_strict(col) {
+                                match self.array.col_bounds(col) {
+                                        (1, 1)   => {
+                                                cnf.add(lits.clone());
+                                                cnf.at_most_one(&lits, encoding);
+                                        }
+                                        (0, 1)   => cnf.at_most_one(&lits, encoding),
+                                        (lo, hi) => cnf.cardinality(&lits, lo, hi),
+                                }
+                                continue;
+                        }
+                        let exclusive = exclusive_vars(&mut cnf, members, |cnf, _| cnf.new_var(), |cnf, var, shared| cnf.add(vec![-var, shared]));
+                        cnf.at_most_one(&exclusive, encoding);
+                }
+
+                writeln!(out, "c Variables 1 to {} are the rows, the rest belong to the encoding.", rows.len())?;
+                writeln!(out, "p cnf {} {}", cnf.num_vars, cnf.clauses.len())?;
+                for clause in &cnf.clauses {
+                        for lit in clause {
+                                write!(out, "{lit} ")?;
+                        }
+                        writeln!(out, "0")?;
+                }
+                Ok (rows)
+        }
+}
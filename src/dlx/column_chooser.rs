@@ -0,0 +1,160 @@
+use super::rng::Rng;
+
+// A strict column that the search could branch on next.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub
+struct ColumnInfo {
+        // The index of the column: strict columns in the order they were given at construction,
+        // then optional ones, then columns added later.
+        pub index:      usize,
+
+        // The number of rows that can still cover the column.
+        pub size:       u64,
+
+        // The number of branches we get when branching on this column.
+        // Equal to size, unless the column has bounds other than (1, 1).
+        pub branches:   u64,
+}
+
+// Decides which column the search branches on.
+// Every choice gives correct results, but a good one keeps the search tree small.
+pub
+trait ColumnChooser: Send + Sync {
+        // Is given every strict column that is not covered yet, in construction order,
+        // and never an empty slice. Returns the position of the chosen column in cols.
+        fn choose (&mut self, cols: &[ColumnInfo]) -> usize;
+
+        // A copy for a fork of the array, see parallel.rs.
+        // Choosers that cannot be copied return None, and the fork uses Mrv instead.
+        fn fork (&self) -> Option<Box<dyn ColumnChooser>>
+        {
+                None
+        }
+}
+
+// Any closure over the column information works as a chooser.
+impl <F: FnMut(&[ColumnInfo]) -> usize + Send + Sync> ColumnChooser for F {
+        fn choose (&mut self, cols: &[ColumnInfo]) -> usize
+        {
+                self(cols)
+        }
+}
+
+// The column with the fewest branches, the first one of them on ties.
+// This is what the solver does by default.
+#[derive(Clone, Copy, Default, Debug)]
+pub
+struct Mrv;
+
+impl ColumnChooser for Mrv {
+        fn choose (&mut self, cols: &[ColumnInfo]) -> usize
+        {
+                let mut best = 0;
+                for (i, col) in cols.iter().enumerate() {
+                        if col.branches < cols[best].branches {
+                                best = i;
+                        }
+                }
+                best
+        }
+
+        fn fork (&self) -> Option<Box<dyn ColumnChooser>>
+        {
+                Some (Box::new(*self))
+        }
+}
+
+// Simply the first column that is not covered yet.
+#[derive(Clone, Copy, Default, Debug)]
+pub
+struct Leftmost;
+
+impl ColumnChooser for Leftmost {
+        fn choose (&mut self, _cols: &[ColumnInfo]) -> usize
+        {
+                0
+        }
+
+        fn fork (&self) -> Option<Box<dyn ColumnChooser>>
+        {
+                Some (Box::new(*self))
+        }
+}
+
+// Like Mrv, but picks uniformly among the columns with the fewest branches,
+// with a seeded generator so the choices are reproducible.
+#[derive(Clone, Debug)]
+pub
+struct MrvRandom {
+        rng: Rng,
+}
+
+impl MrvRandom {
+        pub
+        fn new (seed: u64) -> MrvRandom
+        {
+                MrvRandom {rng: Rng::new(seed)}
+        }
+}
+
+impl ColumnChooser for MrvRandom {
+        fn choose (&mut self, cols: &[ColumnInfo]) -> usize
+        {
+                // Reservoir sampling over the ties, so we need only one pass.
+                let mut best = 0;
+                let mut num_ties = 1;
+                for (i, col) in cols.iter().enumerate().skip(1) {
+                        if col.branches < cols[best].branches {
+                                best = i;
+                                num_ties = 1;
+                        } else if col.branches == cols[best].branches {
+                                num_ties += 1;
+                                if self.rng.below(num_ties) == 0 {
+                                        best = i;
+                                }
+                        }
+                }
+                best
+        }
+
+        fn fork (&self) -> Option<Box<dyn ColumnChooser>>
+        {
+                Some (Box::new(self.clone()))
+        }
+}
+
+// The column with the highest user-assigned priority,
+// and among those the one with the fewest branches.
+// priorities[i] belongs to column i. Columns without a priority, e.g. added later, get 0.
+#[derive(Clone, Debug)]
+pub
+struct Priority {
+        priorities: Box<[i64]>,
+}
+
+impl Priority {
+        pub
+        fn new (priorities: &[i64]) -> Priority
+        {
+                Priority {priorities: priorities.into()}
+        }
+}
+
+impl ColumnChooser for Priority {
+        fn choose (&mut self, cols: &[ColumnInfo]) -> usize
+        {
+                let key = |col: &ColumnInfo| (std::cmp::Reverse(self.priorities.get(col.index).copied().unwrap_or(0)), col.branches);
+                let mut best = 0;
+                for (i, col) in cols.iter().enumerate() {
+                        if key(col) < key(&cols[best]) {
+                                best = i;
+                        }
+                }
+                best
+        }
+
+        fn fork (&self) -> Option<Box<dyn ColumnChooser>>
+        {
+                Some (Box::new(self.clone()))
+        }
+}
@@ -1,12 +1,26 @@
+use super::column_chooser::{ColumnChooser, ColumnInfo, MrvRandom};
+use super::row_order::{ByKey, RowInfo, RowOrder, Rows, Shuffle};
+use super::rng::Rng;
+use super::limits::{Limits, Outcome};
+use super::stats::{Progress, SearchStats};
+
 const UNSAFE_INDEXING: bool = true;
 
 pub
 type NodeIdx = u32;
 
-// Headers will have non-valid indices.
+// Headers will have non-valid indices, and so will the nodes of deleted rows.
 // We just use this constant to represent that.
+pub
 const INVALID_NODE_IDX: NodeIdx = NodeIdx::MAX;
 
+// Optional columns can be colored per node.
+// Rows may share an optional column if they give it the same color,
+// while an uncolored node claims the column for its row alone.
+// Strict columns are never colored.
+// The colors are kept apart from the nodes, see colors, so the nodes stay small.
+const NO_COLOR: u32 = 0;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct Node {
         u:      NodeIdx,
@@ -16,7 +30,7 @@ struct Node {
 
         // We Keep track of the row index.
         row:    NodeIdx,
-        col:    NodeIdx
+        col:    NodeIdx,
 }
 
 pub
@@ -25,35 +39,90 @@ struct DancingLinkArray {
         // There are "strict" and "optional" headers.
         // When producing the horizontal structure on the headers,
         // The optional headers are completely ignored.
-        nodes: Box<[Node]>,
+        // Nodes of deleted rows stay in place, and are reused for rows added later, see free.
+        nodes: Vec<Node>,
+
+        // Free slots in nodes, left behind by deleted rows.
+        free: Vec<NodeIdx>,
 
-        // total number of headers, root, strict and optional.
-        num_headers: usize,
+        // The color of each node, by node index, if colored is set. Empty otherwise.
+        colors: Vec<u32>,
 
-        // One past the end of stict headers, even if there are no optional columns.
-        // We save this because we need to know if a header is strict or optional.
-        first_optional_h_idx: NodeIdx,
+        // Whether any node was ever given a color.
+        // If not, we use the plain cover loop, which does not look at colors at all.
+        colored: bool,
+
+        // The number of rows ever constructed or added, including deleted ones.
+        // Row indices are never reused, so that anything kept per row stays valid.
+        num_rows: usize,
+
+        // The header of each column. At construction, column i has header i + 1,
+        // but columns added later get theirs wherever there is room in nodes.
+        headers: Vec<NodeIdx>,
+
+        // Whether each column is strict.
+        // We need to know this for headers, which are in the horizontal structure only if they are.
+        strict: Vec<bool>,
 
         // Size for each column. Not really needed for optional columns,
         // but easier to just have for all.
-        sizes: Box<[u64]>,
+        sizes: Vec<u64>,
+
+        // For each optional column, the number of chosen rows that purified it.
+        // Only the first of those rows hides the rows of other colors,
+        // and only the last of them to be taken back unhides them.
+        purifications: Vec<u32>,
+
+        // For each strict column, how often it has to be covered, as an inclusive range.
+        // This is (1, 1) unless set_bounds says otherwise.
+        bounds: Vec<(u32, u32)>,
+
+        // For each strict column, how often it is covered by the currently removed rows.
+        // Only kept up to date if multiplicities is set.
+        counts: Vec<u32>,
+
+        // Whether any column has bounds other than (1, 1).
+        // If not, we use the plain exact cover algorithm, which is a bit faster.
+        multiplicities: bool,
+
+        // Picks the column to branch on.
+        // If not set, we use the column with the fewest branches, see lowest_strict_header.
+        chooser: Option<Box<dyn ColumnChooser>>,
+
+        // Scratch space to hand the uncovered columns to the chooser.
+        col_buf: Vec<ColumnInfo>,
+
+        // Reorders the rows of the column we branch on.
+        // If not set, we try them in the order of the links, which is construction order.
+        row_order: Option<Box<dyn RowOrder>>,
+
+        // The number of times a node was unlinked or relinked, ever.
+        // Only the link primitives like rm_node_ver count these,
+        // and a search counts its own by the difference, see Search::step_budgeted.
+        updates: u64,
+
+        // The statistics of the last search that finished or was abandoned.
+        last_stats: SearchStats,
 
+        // The cost of each row, for solve_min_cost. If not set, every row costs 1.
+        costs: Option<Vec<u64>>,
 }
 
 impl DancingLinkArray {
 
         // Some utilities.
+        // Iterates over the nodes of rows that were not deleted.
+        // Headers do not belong to a row either, so they are skipped as well.
         fn it_over_node_idc (&self) -> impl Iterator<Item = NodeIdx>
         {
-                let n_h = self.num_headers      as NodeIdx;
                 let n_all = self.nodes.len()    as NodeIdx;
-                n_h .. n_all
+                (0 .. n_all).filter(|&idx| self.nodes[idx as usize].row != INVALID_NODE_IDX)
         }
 
         fn rm_node_hor (&mut self, n_idx: NodeIdx)
         {
                 let n_idx = n_idx as usize;
-                let nodes = &mut self.nodes;
+                let nodes = &mut self.nodes[..];
                 if UNSAFE_INDEXING {
                         unsafe {
                         nodes.get_unchecked_mut(nodes.get_unchecked(n_idx).l as usize).r
@@ -65,12 +134,13 @@ impl DancingLinkArray {
                         nodes[nodes[n_idx].l as usize].r = nodes[n_idx].r;
                         nodes[nodes[n_idx].r as usize].l = nodes[n_idx].l;
                 }
+                self.updates += 1;
         }
 
         fn rm_node_ver (&mut self, n_idx: NodeIdx)
         {
                 let n_idx = n_idx as usize;
-                let nodes = &mut self.nodes;
+                let nodes = &mut self.nodes[..];
                 if UNSAFE_INDEXING {
                         unsafe {
                         nodes.get_unchecked_mut(nodes.get_unchecked(n_idx).u as usize).d
@@ -82,10 +152,11 @@ impl DancingLinkArray {
                         nodes[nodes[n_idx].u as usize].d = nodes[n_idx].d;
                         nodes[nodes[n_idx].d as usize].u = nodes[n_idx].u;
                 }
+                self.updates += 1;
         }
         fn insert_node_hor (&mut self, n_idx: NodeIdx)
         {
-                let nodes = &mut self.nodes;
+                let nodes = &mut self.nodes[..];
                 if UNSAFE_INDEXING {
                         unsafe {
                         nodes.get_unchecked_mut(nodes.get_unchecked(n_idx as usize).l as usize).r = n_idx;
@@ -95,10 +166,11 @@ impl DancingLinkArray {
                         nodes[nodes[n_idx as usize].l as usize].r = n_idx;
                         nodes[nodes[n_idx as usize].r as usize].l = n_idx;
                 }
+                self.updates += 1;
         }
         fn insert_node_ver (&mut self, n_idx: NodeIdx)
         {
-                let nodes = &mut self.nodes;
+                let nodes = &mut self.nodes[..];
                 if UNSAFE_INDEXING {
                         unsafe {
                         nodes.get_unchecked_mut(nodes.get_unchecked(n_idx as usize).u as usize).d = n_idx;
@@ -108,6 +180,7 @@ impl DancingLinkArray {
                         nodes[nodes[n_idx as usize].u as usize].d = n_idx;
                         nodes[nodes[n_idx as usize].d as usize].u = n_idx;
                 }
+                self.updates += 1;
         }
 
         fn to_bottom (&self, idx: NodeIdx) -> NodeIdx
@@ -142,9 +215,14 @@ impl DancingLinkArray {
         }
         fn to_header (&self, n_idx: NodeIdx) -> NodeIdx
         {
-                // The first node in the self.nodes array
-                // is root. Therefore, col 0 is indexed at 1.
-                self.get_col(n_idx) + 1
+                let col = self.get_col(n_idx) as usize;
+                if UNSAFE_INDEXING {
+                        unsafe {
+                        *self.headers.get_unchecked(col)
+                        }
+                } else {
+                        self.headers[col]
+                }
         }
 
         fn get_col (&self, idx: NodeIdx) -> NodeIdx
@@ -157,6 +235,19 @@ impl DancingLinkArray {
                         self.nodes[idx as usize].col
                 }
         }
+        fn get_color (&self, idx: NodeIdx) -> u32
+        {
+                if !self.colored {
+                        return NO_COLOR;
+                }
+                if UNSAFE_INDEXING {
+                        unsafe {
+                        *self.colors.get_unchecked(idx as usize)
+                        }
+                } else {
+                        self.colors[idx as usize]
+                }
+        }
         fn get_row (&self, idx: NodeIdx) -> NodeIdx
         {
                 if UNSAFE_INDEXING {
@@ -173,27 +264,60 @@ impl DancingLinkArray {
                 0
         }
 
-        /*
+        pub
         fn num_cols (&self) -> usize
         {
-                self.num_headers - 1
+                self.headers.len()
+        }
+
+        pub
+        fn is_strict (&self, col: usize) -> bool
+        {
+                self.strict[col]
+        }
+
+        // How often strict column col has to be covered, see set_bounds.
+        pub
+        fn col_bounds (&self, col: usize) -> (u32, u32)
+        {
+                self.bounds[col]
+        }
+
+        // The columns of the row of n_idx, with their colors, in column order.
+        pub
+        fn row_cols (&self, n_idx: NodeIdx) -> Vec<(usize, u32)>
+        {
+                let mut cols = vec![(self.get_col(n_idx) as usize, self.get_color(n_idx))];
+                let mut idx = self.to_right(n_idx);
+                while idx != n_idx {
+                        cols.push((self.get_col(idx) as usize, self.get_color(idx)));
+                        idx = self.to_right(idx);
+                }
+                cols.sort_unstable();
+                cols
         }
-        */
 
         fn num_rows (&self) -> usize
         {
-                self.nodes.last().map(|nd| (nd.row as usize) + 1).unwrap_or(0)
+                self.num_rows
         }
 
         fn is_header (&self, idx: NodeIdx) -> bool
         {
-                (idx as usize) < self.num_headers
+                idx == self.root() || self.to_header(idx) == idx
         }
 
-        // We count the root header as being strict.
+        // Not meaningful for root.
         fn header_in_hor_structure (&self, h_idx: NodeIdx) -> bool
         {
-                h_idx < self.first_optional_h_idx
+                let col = self.get_col(h_idx) as usize;
+                if UNSAFE_INDEXING {
+                        unsafe {
+                        *self.strict.get_unchecked(col)
+                        }
+                } else {
+                        self.strict[col]
+                }
         }
 
         fn get_size_node (&self, idx: NodeIdx) -> u64
@@ -220,6 +344,27 @@ impl DancingLinkArray {
                 }
         }
 
+        // Removes every node in the row of v_idx from its column, except v_idx itself.
+        fn hide_row (&mut self, v_idx: NodeIdx)
+        {
+                let mut h_idx = self.to_right(v_idx);
+                while h_idx != v_idx {
+                        self.rm_node_ver(h_idx);
+                        *self.get_size_node_mut(h_idx) -= 1;
+                        h_idx = self.to_right(h_idx);
+                }
+        }
+
+        fn unhide_row (&mut self, v_idx: NodeIdx)
+        {
+                let mut h_idx = self.to_right(v_idx);
+                while h_idx != v_idx {
+                        self.insert_node_ver(h_idx);
+                        *self.get_size_node_mut(h_idx) += 1;
+                        h_idx = self.to_right(h_idx);
+                }
+        }
+
         fn cover_col (&mut self, c: NodeIdx)
         {
                 debug_assert!(self.is_header(c));
@@ -233,13 +378,7 @@ impl DancingLinkArray {
                 let mut v_idx = self.to_bottom(c);
 
                 while v_idx != c {
-                        // Now we remove this node's row from their column
-                        let mut h_idx = self.to_right(v_idx);
-                        while h_idx != v_idx {
-                                self.rm_node_ver(h_idx);
-                                *self.get_size_node_mut(h_idx) -= 1;
-                                h_idx = self.to_right(h_idx);
-                        }
+                        self.hide_row(v_idx);
                         v_idx = self.to_bottom(v_idx);
                 }
         }
@@ -250,13 +389,7 @@ impl DancingLinkArray {
 
                 let mut v_idx = self.to_bottom(c);
                 while v_idx != c {
-                        // Now we insert this node's row from their column
-                        let mut h_idx = self.to_right(v_idx);
-                        while h_idx != v_idx {
-                                self.insert_node_ver(h_idx);
-                                *self.get_size_node_mut(h_idx) += 1;
-                                h_idx = self.to_right(h_idx);
-                        }
+                        self.unhide_row(v_idx);
                         v_idx = self.to_bottom(v_idx);
                 }
 
@@ -265,10 +398,84 @@ impl DancingLinkArray {
                 }
         }
 
-        // Covers each column in the row of n_idx
+        // The colored node n_idx was chosen, so its optional column
+        // can from now on only be shared with rows of the same color.
+        // Rows of the other colors are hidden, but stay in the column itself.
+        fn purify (&mut self, n_idx: NodeIdx)
+        {
+                let c = self.to_header(n_idx);
+                let cnt = self.get_purifications_mut(c);
+                *cnt += 1;
+                if *cnt > 1 {
+                        // Some earlier row already did this, with the same color.
+                        return;
+                }
+
+                let color = self.get_color(n_idx);
+                let mut v_idx = self.to_bottom(c);
+                while v_idx != c {
+                        if self.get_color(v_idx) != color {
+                                self.hide_row(v_idx);
+                        }
+                        v_idx = self.to_bottom(v_idx);
+                }
+        }
+
+        fn unpurify (&mut self, n_idx: NodeIdx)
+        {
+                let c = self.to_header(n_idx);
+                let cnt = self.get_purifications_mut(c);
+                *cnt -= 1;
+                if *cnt > 0 {
+                        return;
+                }
+
+                let color = self.get_color(n_idx);
+                let mut v_idx = self.to_bottom(c);
+                while v_idx != c {
+                        if self.get_color(v_idx) != color {
+                                self.unhide_row(v_idx);
+                        }
+                        v_idx = self.to_bottom(v_idx);
+                }
+        }
+
+        fn get_purifications_mut (&mut self, h_idx: NodeIdx) -> &mut u32
+        {
+                let col = self.get_col(h_idx) as usize;
+                &mut self.purifications[col]
+        }
+
+        // Chooses the row of n_idx.
         // public, since the DLXsolver may want to manually remove rows.
         pub
         fn rm_row (&mut self, n_idx: NodeIdx)
+        {
+                if self.multiplicities {
+                        self.rm_row_counted(n_idx);
+                } else if self.colored {
+                        self.rm_row_colored(n_idx);
+                } else {
+                        self.rm_row_exact(n_idx);
+                }
+        }
+
+        // public, since the DLXsolver may want to manually insert rows.
+        // Rows must be inserted in precisely the opposite order of removal.
+        pub
+        fn insert_row (&mut self, n_idx: NodeIdx)
+        {
+                if self.multiplicities {
+                        self.insert_row_counted(n_idx);
+                } else if self.colored {
+                        self.insert_row_colored(n_idx);
+                } else {
+                        self.insert_row_exact(n_idx);
+                }
+        }
+
+        // Covers each column in the row of n_idx.
+        fn rm_row_exact (&mut self, n_idx: NodeIdx)
         {
                 let last_to_remove = self.to_left(n_idx);
                 let mut hor_it_idx = n_idx;
@@ -282,10 +489,7 @@ impl DancingLinkArray {
                 }
         }
 
-        // public, since the DLXsolver may want to manually insert rows.
-        // Rows must be inserted in precisely the opposite order of removal.
-        pub
-        fn insert_row (&mut self, n_idx: NodeIdx)
+        fn insert_row_exact (&mut self, n_idx: NodeIdx)
         {
                 // We have to insert the columns in the opposite
                 // order in which we removed them.
@@ -302,6 +506,374 @@ impl DancingLinkArray {
                 }
         }
 
+        // Like rm_row_exact, but purifies the columns the row colors instead of covering them.
+        fn rm_row_colored (&mut self, n_idx: NodeIdx)
+        {
+                let last_to_remove = self.to_left(n_idx);
+                let mut hor_it_idx = n_idx;
+                loop {
+                        if self.get_color(hor_it_idx) == NO_COLOR {
+                                let c = self.to_header(hor_it_idx);
+                                self.cover_col(c);
+                        } else {
+                                self.purify(hor_it_idx);
+                        }
+                        if hor_it_idx == last_to_remove {
+                                break;
+                        }
+                        hor_it_idx = self.to_right(hor_it_idx);
+                }
+        }
+
+        fn insert_row_colored (&mut self, n_idx: NodeIdx)
+        {
+                let entry_idx = self.to_left(n_idx);
+                let mut hor_it_idx = entry_idx;
+                loop {
+                        if self.get_color(hor_it_idx) == NO_COLOR {
+                                let c = self.to_header(hor_it_idx);
+                                self.uncover_col(c);
+                        } else {
+                                self.unpurify(hor_it_idx);
+                        }
+
+                        hor_it_idx = self.to_left(hor_it_idx);
+                        if hor_it_idx == entry_idx {
+                                break;
+                        }
+                }
+        }
+
+        // Removes the whole row of n_idx from the columns, including n_idx itself.
+        // Used when a row is chosen or ruled out, while its columns stay available for other rows.
+        fn exclude_row (&mut self, n_idx: NodeIdx)
+        {
+                self.rm_node_ver(n_idx);
+                *self.get_size_node_mut(n_idx) -= 1;
+                self.hide_row(n_idx);
+        }
+
+        fn include_row (&mut self, n_idx: NodeIdx)
+        {
+                self.unhide_row(n_idx);
+                self.insert_node_ver(n_idx);
+                *self.get_size_node_mut(n_idx) += 1;
+        }
+
+        // Like rm_row_exact, but strict columns may be covered several times.
+        // The row itself is taken out first, and a strict column is
+        // only covered once it reaches its upper bound.
+        fn rm_row_counted (&mut self, n_idx: NodeIdx)
+        {
+                self.exclude_row(n_idx);
+                let last_to_remove = self.to_left(n_idx);
+                let mut hor_it_idx = n_idx;
+                loop {
+                        let c = self.to_header(hor_it_idx);
+                        if self.get_color(hor_it_idx) != NO_COLOR {
+                                self.purify(hor_it_idx);
+                        } else if self.header_in_hor_structure(c) {
+                                let col = self.get_col(c) as usize;
+                                self.counts[col] += 1;
+                                if self.counts[col] == self.bounds[col].1 {
+                                        self.cover_col(c);
+                                }
+                        } else {
+                                self.cover_col(c);
+                        }
+                        if hor_it_idx == last_to_remove {
+                                break;
+                        }
+                        hor_it_idx = self.to_right(hor_it_idx);
+                }
+        }
+
+        fn insert_row_counted (&mut self, n_idx: NodeIdx)
+        {
+                let entry_idx = self.to_left(n_idx);
+                let mut hor_it_idx = entry_idx;
+                loop {
+                        let c = self.to_header(hor_it_idx);
+                        if self.get_color(hor_it_idx) != NO_COLOR {
+                                self.unpurify(hor_it_idx);
+                        } else if self.header_in_hor_structure(c) {
+                                let col = self.get_col(c) as usize;
+                                if self.counts[col] == self.bounds[col].1 {
+                                        self.uncover_col(c);
+                                }
+                                self.counts[col] -= 1;
+                        } else {
+                                self.uncover_col(c);
+                        }
+
+                        hor_it_idx = self.to_left(hor_it_idx);
+                        if hor_it_idx == entry_idx {
+                                break;
+                        }
+                }
+                self.include_row(n_idx);
+        }
+
+        // How many more rows strict header h_idx needs at least, and may take at most.
+        fn remaining_bounds (&self, h_idx: NodeIdx) -> (u64, u64)
+        {
+                let col = self.get_col(h_idx) as usize;
+                let (lo, hi) = self.bounds[col];
+                let cnt = self.counts[col];
+                (u64::from(lo.saturating_sub(cnt)), u64::from(hi - cnt))
+        }
+
+        // The number of branches we get when branching on strict header h_idx.
+        // If the column needs another `need` rows, the first of them has to be
+        // among the first size - need + 1 rows, and if it needs none,
+        // there is an extra branch in which we do not use it anymore.
+        fn branch_degree (&self, h_idx: NodeIdx) -> u64
+        {
+                let (need, _) = self.remaining_bounds(h_idx);
+                (self.get_size_node(h_idx) + 1).saturating_sub(need)
+        }
+
+        // Starting from candidate node n_idx in header h_idx, returns what to try next:
+        // a node whose row we choose, h_idx itself to stop using the column,
+        // or nothing if the column is exhausted.
+        fn branch_from (&self, h_idx: NodeIdx, n_idx: NodeIdx) -> Option<NodeIdx>
+        {
+                if !self.multiplicities {
+                        return (n_idx != h_idx).then_some(n_idx);
+                }
+                let (need, _) = self.remaining_bounds(h_idx);
+                if n_idx != h_idx {
+                        // The rows we have tried are ruled out,
+                        // so the column has to be filled by the remaining ones.
+                        (self.get_size_node(h_idx) >= need).then_some(n_idx)
+                } else {
+                        (need == 0).then_some(h_idx)
+                }
+        }
+
+        // If there is a row order, appends the rows of header h_idx to buf,
+        // in the order they should be tried, and returns true.
+        // Otherwise the rows are tried in the order of the links, and we return false.
+        fn order_rows (&mut self, h_idx: NodeIdx, buf: &mut Vec<RowInfo>) -> bool
+        {
+                if self.row_order.is_none() {
+                        return false;
+                }
+
+                let start = buf.len();
+                let mut v_idx = self.to_bottom(h_idx);
+                while v_idx != h_idx {
+                        buf.push(RowInfo {index: self.get_row(v_idx) as usize, node: v_idx});
+                        v_idx = self.to_bottom(v_idx);
+                }
+
+                let col = self.get_col(h_idx) as usize;
+                let row_order = self.row_order.as_mut().expect("Checked above");
+                row_order.order(col, &mut Rows::new(&mut buf[start..]));
+                true
+        }
+
+        // Sets the order in which the rows of a column are tried,
+        // and returns the previous one. None restores the default.
+        pub
+        fn set_row_order (&mut self, row_order: Option<Box<dyn RowOrder>>) -> Option<Box<dyn RowOrder>>
+        {
+                std::mem::replace(&mut self.row_order, row_order)
+        }
+
+        // The statistics of the last search that finished or was abandoned.
+        pub
+        fn last_stats (&self) -> &SearchStats
+        {
+                &self.last_stats
+        }
+
+        pub
+        fn set_last_stats (&mut self, stats: SearchStats)
+        {
+                self.last_stats = stats;
+        }
+
+        // Sets the cost of each row, used by solve_min_cost.
+        // costs[r] belongs to row r.
+        pub
+        fn set_row_costs (&mut self, costs: Vec<u64>)
+        {
+                assert_eq!(costs.len(), self.num_rows(), "Need a cost for every row");
+                self.costs = Some (costs);
+        }
+
+        // Sets the cost of a row added after the costs were set.
+        // Rows added in between, that were not given one, cost 1.
+        pub
+        fn set_row_cost (&mut self, row: usize, cost: u64)
+        {
+                let costs = self.costs.get_or_insert_with(Vec::new);
+                if costs.len() <= row {
+                        costs.resize(row + 1, 1);
+                }
+                costs[row] = cost;
+        }
+
+        // Rows without a cost cost 1.
+        fn row_cost (&self, row: NodeIdx) -> u64
+        {
+                self.costs.as_ref().and_then(|costs| costs.get(row as usize)).copied().unwrap_or(1)
+        }
+
+        // Spreads the cost of each row evenly over its strict columns, scaled by COST_SCALE.
+        // Rows without strict columns get nothing, as they are never chosen.
+        fn cost_shares (&self) -> Box<[u128]>
+        {
+                let mut num_strict = vec![0u128; self.num_rows()];
+                for n_idx in self.it_over_node_idc() {
+                        if self.header_in_hor_structure(self.to_header(n_idx)) {
+                                num_strict[self.get_row(n_idx) as usize] += 1;
+                        }
+                }
+                num_strict.iter().enumerate().map(|(row, &n)| {
+                        (u128::from(self.row_cost(row as NodeIdx)) * COST_SCALE).checked_div(n).unwrap_or(0)
+                }).collect()
+        }
+
+        // A lower bound on the cost of the rows still needed, scaled by COST_SCALE.
+        // Every strict column that is not covered yet still needs some rows,
+        // each of which pays at least the cheapest share in that column.
+        fn cost_lower_bound (&self, shares: &[u128]) -> u128
+        {
+                let mut bound = 0;
+                let mut h_idx = self.to_right(self.root());
+                while h_idx != self.root() {
+                        let (need, _) = self.remaining_bounds(h_idx);
+                        if need > 0 {
+                                let mut cheapest = None;
+                                let mut v_idx = self.to_bottom(h_idx);
+                                while v_idx != h_idx {
+                                        let share = shares[self.get_row(v_idx) as usize];
+                                        cheapest = Some (cheapest.map_or(share, |c: u128| c.min(share)));
+                                        v_idx = self.to_bottom(v_idx);
+                                }
+                                // An empty column is a dead end, which the search finds by itself.
+                                bound = (u128::from(need) * cheapest.unwrap_or(0)).saturating_add(bound);
+                        }
+                        h_idx = self.to_right(h_idx);
+                }
+                bound
+        }
+
+        // The total cost of the given rows, which stops at u64::MAX instead of overflowing.
+        fn total_cost (&self, rows: impl Iterator<Item = NodeIdx>) -> u64
+        {
+                rows.fold(0, |total: u64, row| total.saturating_add(self.row_cost(row)))
+        }
+
+        // Returns a solution with the lowest total cost, and that cost.
+        // Of the cheapest solutions, the one found first by the search is returned,
+        // which only depends on the heuristics.
+        // If no row order was set, rows are tried from cheap to expensive,
+        // as that tends to find good solutions early, which prunes more.
+        // If a limit is hit, the best solution so far is dropped, as it may not be the cheapest.
+        pub
+        fn solve_min_cost (&mut self, limits: &Limits, mut progress: Option<&mut Progress>) -> Outcome <(Vec<NodeIdx>, u64)>
+        {
+                let saved = if self.row_order.is_none() {
+                        let costs = (0..self.num_rows()).map(|row| self.row_cost(row as NodeIdx)).collect();
+                        Some (self.set_row_order(Some (Box::new(ByKey::new(costs)))))
+                } else {
+                        None
+                };
+
+                let shares = self.cost_shares();
+                let mut best: Option<(Vec<NodeIdx>, u64)> = None;
+                let mut search = Search::new();
+                let prune = |dla: &DancingLinkArray, search: &Search, best: &Option<(Vec<NodeIdx>, u64)>| {
+                        let Some((_, best_cost)) = best else {
+                                return false;
+                        };
+                        let cost = dla.total_cost(search.chosen_rows(dla));
+                        // Only strictly cheaper solutions are of interest, so ties are pruned as well.
+                        (u128::from(cost) * COST_SCALE).saturating_add(dla.cost_lower_bound(&shares)) >= u128::from(*best_cost) * COST_SCALE
+                };
+                let finished = loop {
+                        match search.next_solution_pruned(self, limits, |dla, search| prune(dla, search, &best), progress.as_deref_mut()) {
+                                Some (true)  => {
+                                        let sol: Vec<NodeIdx> = search.chosen_rows(self).collect();
+                                        let cost = self.total_cost(sol.iter().copied());
+                                        best = Some ((sol, cost));
+                                }
+                                Some (false) => break true,
+                                None         => {
+                                        search.reset(self);
+                                        break false;
+                                }
+                        }
+                };
+                self.last_stats = search.take_stats();
+
+                if let Some(row_order) = saved {
+                        self.set_row_order(row_order);
+                }
+                match (finished, best) {
+                        (false, _)          => Outcome::Aborted,
+                        (true, Some (best)) => Outcome::Solved(best),
+                        (true, None)        => Outcome::Unsatisfiable,
+                }
+        }
+
+        // Sets both heuristics at once, and returns the previous ones.
+        pub
+        fn set_heuristics (&mut self, heuristics: Heuristics) -> Heuristics
+        {
+                Heuristics {
+                        chooser: self.set_chooser(heuristics.chooser),
+                        row_order: self.set_row_order(heuristics.row_order),
+                }
+        }
+
+        // Takes the branch n_idx of header h_idx, see branch_from.
+        fn enter_branch (&mut self, h_idx: NodeIdx, n_idx: NodeIdx)
+        {
+                if n_idx == h_idx {
+                        // No more rows for this column. Covering it rules out the ones that are left.
+                        self.cover_col(h_idx);
+                } else {
+                        self.rm_row(n_idx);
+                }
+        }
+
+        fn leave_branch (&mut self, h_idx: NodeIdx, n_idx: NodeIdx)
+        {
+                if n_idx == h_idx {
+                        self.uncover_col(h_idx);
+                } else {
+                        self.insert_row(n_idx);
+                }
+        }
+
+        // Whether, after trying a row in header h_idx, we have to rule it out
+        // for the following branches.
+        // Only needed if the column stays available after choosing the row,
+        // because otherwise it covers all the other rows anyway.
+        fn must_exclude (&self, h_idx: NodeIdx) -> bool
+        {
+                self.multiplicities && self.remaining_bounds(h_idx).1 > 1
+        }
+
+        // Sets how often each strict column has to be covered,
+        // as an inclusive range per strict column, in column order.
+        // Must be called before any row is removed.
+        pub
+        fn set_bounds (&mut self, bounds: &[(u32, u32)])
+        {
+                let strict_cols: Vec<usize> = (0..self.num_cols()).filter(|&col| self.strict[col]).collect();
+                assert_eq!(bounds.len(), strict_cols.len(), "Need bounds for every strict column");
+                assert!(bounds.iter().all(|&(lo, hi)| lo <= hi && hi > 0), "Invalid column bounds given");
+                for (&col, &b) in strict_cols.iter().zip(bounds) {
+                        self.bounds[col] = b;
+                }
+                self.multiplicities = self.bounds.iter().any(|&b| b != (1, 1));
+        }
+
         // Finds strict header with the lowest column size.
         // If there is only root, finds nothing.
         fn lowest_strict_header (&self) -> Option <NodeIdx>
@@ -311,6 +883,10 @@ impl DancingLinkArray {
                         return None;
                 }
 
+                if self.multiplicities {
+                        return self.lowest_degree_header();
+                }
+
                 // We save the current "best" index and its count.
                 let mut lowest_idx   = h_idx;
                 let mut lowest_count = self.get_size_node(h_idx);
@@ -329,79 +905,160 @@ impl DancingLinkArray {
                 Some(lowest_idx)
         }
 
-        // Returns the first solution found.
-        // A solutions is a vector of row indices.
-        pub
-        fn solve_one (&mut self) -> Option <Vec<NodeIdx>>
+        // Picks the strict header to branch on, using the chooser if there is one.
+        // If there is only root, finds nothing.
+        fn choose_header (&mut self) -> Option <NodeIdx>
         {
-                // First we find the strict header with the lowest index.
-                let Some(lowest_c) = self.lowest_strict_header() else {
-                        // No columns! We're done.
-                        return Some(Vec::new());
-                };
+                if self.chooser.is_none() {
+                        return self.lowest_strict_header();
+                }
 
-                let mut v_idx = self.to_bottom(lowest_c);
-                while v_idx != lowest_c {
+                self.col_buf.clear();
+                let mut h_idx = self.to_right(self.root());
+                while h_idx != self.root() {
+                        let info = ColumnInfo {
+                                index: self.get_col(h_idx) as usize,
+                                size: self.get_size_node(h_idx),
+                                branches: self.branch_degree(h_idx),
+                        };
+                        self.col_buf.push(info);
+                        h_idx = self.to_right(h_idx);
+                }
+                if self.col_buf.is_empty() {
+                        return None;
+                }
+
+                let chooser = self.chooser.as_mut().expect("Checked above");
+                let pos = chooser.choose(&self.col_buf);
+                Some (self.headers[self.col_buf[pos].index])
+        }
 
-                        self.rm_row(v_idx);
-                        let opt_sub_sol = self.solve_one();
-                        self.insert_row(v_idx);
+        // Sets the heuristic used to pick the column to branch on,
+        // and returns the previous one. None restores the default.
+        pub
+        fn set_chooser (&mut self, chooser: Option<Box<dyn ColumnChooser>>) -> Option<Box<dyn ColumnChooser>>
+        {
+                std::mem::replace(&mut self.chooser, chooser)
+        }
 
-                        if let Some(mut sub_sol) = opt_sub_sol {
-                                let r = self.get_row(v_idx);
-                                sub_sol.push(r);
-                                return Some(sub_sol);
+        // Like lowest_strict_header, but for columns with bounds,
+        // where the column size alone does not tell how many branches we get.
+        fn lowest_degree_header (&self) -> Option <NodeIdx>
+        {
+                let mut lowest: Option<(NodeIdx, u64)> = None;
+                let mut h_idx = self.to_right(self.root());
+                while h_idx != self.root() {
+                        let degree = self.branch_degree(h_idx);
+                        if lowest.is_none_or(|(_, d)| degree < d) {
+                                lowest = Some((h_idx, degree));
                         }
-                        v_idx = self.to_bottom(v_idx);
+                        h_idx = self.to_right(h_idx);
                 }
-                None
+                lowest.map(|(h_idx, _)| h_idx)
         }
 
-        // Returns all solutions.
-        // Each solution is a vector of row indices.
+        // Returns the first solution found.
+        // A solutions is a vector of row indices.
+        // Here and below, progress is told about the search now and then, if given.
         pub
-        fn solve_many (&mut self) -> Vec <Vec<NodeIdx>>
+        fn solve_one (&mut self, limits: &Limits, progress: Option<&mut Progress>) -> Outcome <Vec<NodeIdx>>
         {
-                // First we find the header with the lowest index.
-                let Some(lowest_c) = self.lowest_strict_header() else {
-                        // No columns! We're done.
-                        return vec!(Vec::new());
+                let mut search = Search::new();
+                let sol = match search.next_solution_limited(self, limits, progress) {
+                        Some (true)  => Outcome::Solved(search.chosen_rows(self).collect()),
+                        Some (false) => Outcome::Unsatisfiable,
+                        None         => Outcome::Aborted,
                 };
+                search.reset(self);
+                self.last_stats = search.take_stats();
+                sol
+        }
 
+        // Returns all solutions.
+        // Each solution is a vector of row indices.
+        pub
+        fn solve_many (&mut self, limits: &Limits, mut progress: Option<&mut Progress>) -> Outcome <Vec <Vec<NodeIdx>>>
+        {
+                // Every solution contains exactly one row of each column we branch on,
+                // so the solutions found by each branch are distinct.
+                let mut search = Search::new();
                 let mut sols: Vec <Vec<NodeIdx>> = Vec::new();
-
-                let mut v_idx = self.to_bottom(lowest_c);
-                while v_idx != lowest_c {
-                        let r = self.get_row(v_idx);
-                        // We only run the row if no current solution
-                        // contains r.
-                        let skip_r = sols.iter().any(|sol| sol.contains(&r));
-                        if skip_r {
-                                continue;
+                loop {
+                        match search.next_solution_limited(self, limits, progress.as_deref_mut()) {
+                                Some (true)  => sols.push(search.chosen_rows(self).collect()),
+                                Some (false) => break,
+                                None         => {
+                                        search.reset(self);
+                                        self.last_stats = search.take_stats();
+                                        return Outcome::Aborted;
+                                }
                         }
+                }
+                self.last_stats = search.take_stats();
+                if sols.is_empty() {
+                        Outcome::Unsatisfiable
+                } else {
+                        Outcome::Solved(sols)
+                }
+        }
 
-                        self.rm_row(v_idx);
-                        let sub_sols = self.solve_many();
-                        self.insert_row(v_idx);
-
-                        // We add each subsolution to the solutions, after inserting this row.
-                        for mut sub_sol in sub_sols {
-                                sub_sol.push(r);
-                                sols.push(sub_sol);
+        // Counts the solutions, without storing them.
+        // If a cap is given, we stop as soon as we have found that many.
+        // Only a finished search can tell there are none, so a cap of 0 gives Solved(0).
+        pub
+        fn count_solutions (&mut self, cap: Option<u128>, limits: &Limits, mut progress: Option<&mut Progress>) -> Outcome <u128>
+        {
+                let mut search = Search::new();
+                let mut cnt: u128 = 0;
+                let mut finished = false;
+                while cap.is_none_or(|cap| cnt < cap) {
+                        match search.next_solution_limited(self, limits, progress.as_deref_mut()) {
+                                Some (true)  => cnt += 1,
+                                Some (false) => {
+                                        finished = true;
+                                        break;
+                                }
+                                None         => {
+                                        search.reset(self);
+                                        self.last_stats = search.take_stats();
+                                        return Outcome::Aborted;
+                                }
                         }
-                        v_idx = self.to_bottom(v_idx);
                 }
-                sols
+                // We may have stopped halfway, in which case the search still has rows removed.
+                search.reset(self);
+                self.last_stats = search.take_stats();
+                if finished && cnt == 0 {
+                        Outcome::Unsatisfiable
+                } else {
+                        Outcome::Solved(cnt)
+                }
         }
 
-        // The only constructor of the array.
+        // Constructs an array without colors.
         // Meant to be used by the "dlx" module.
         // Assumes the elements are sorted row-major and unique.
         // elems_gen must generate (row, col) pairs.
+        // Rows without elements get no nodes, and to_each_row gives them INVALID_NODE_IDX.
+        // Columns without elements are left empty,
+        // so a strict one can never be covered.
         pub
         fn from_sorted_idc_unsafe <I> (elems_gen: I, num_rows: usize, num_strict_cols: usize, num_opt_cols: usize) -> DancingLinkArray
         where
                 I: IntoIterator<Item = (usize, usize)>
+        {
+                let colored_gen = elems_gen.into_iter().map(|(row, col)| (row, col, NO_COLOR));
+                Self::from_sorted_colored_idc_unsafe(colored_gen, num_rows, num_strict_cols, num_opt_cols)
+        }
+
+        // The only real constructor of the array.
+        // Like from_sorted_idc_unsafe, but elems_gen generates (row, col, color) triples,
+        // where the color is 0 for uncolored nodes.
+        // Only nodes in optional columns may be colored.
+        pub
+        fn from_sorted_colored_idc_unsafe <I> (elems_gen: I, num_rows: usize, num_strict_cols: usize, num_opt_cols: usize) -> DancingLinkArray
+        where
+                I: IntoIterator<Item = (usize, usize, u32)>
         {
                 fn gen_header (col: usize) -> Node
                 {
@@ -415,7 +1072,7 @@ impl DancingLinkArray {
                         }
                 }
 
-                fn gen_node ((row, col): (usize, usize)) -> Node
+                fn gen_node (row: usize, col: usize) -> Node
                 {
                         Node {
                                 u: INVALID_NODE_IDX,
@@ -428,27 +1085,35 @@ impl DancingLinkArray {
                 }
 
                 let num_cols = num_strict_cols + num_opt_cols;
-                let num_headers = num_cols + 1; // one for each column, plus root.
 
                 // By assumption, elems is sorted.
                 let gen_root    = std::iter::once(gen_header(0));
                 let gen_headers = (0..num_cols).map(gen_header);
-                let gen_nodes   = elems_gen.into_iter().map (gen_node);
 
-                let mut nodes: Box<[Node]> = gen_root.chain(gen_headers).chain(gen_nodes).collect();
+                let mut nodes: Vec<Node> = gen_root.chain(gen_headers).collect();
+                let elems_it = elems_gen.into_iter();
+                nodes.reserve(elems_it.size_hint().0);
+                // Only started at the first colored node, so uncolored arrays never have one.
+                let mut colors: Vec<u32> = Vec::new();
+                for (row, col, color) in elems_it {
+                        if color != NO_COLOR && colors.is_empty() {
+                                colors.resize(nodes.len(), NO_COLOR);
+                        }
+                        if !colors.is_empty() {
+                                colors.push(color);
+                        }
+                        nodes.push(gen_node(row, col));
+                }
+                let colored = !colors.is_empty();
 
                 // Offset by 1, because the first header is the root.
                 let last_strict_h_idx = num_strict_cols as NodeIdx;
 
-                // Index of first optional header.
-                // If there are, no optional headers, this value is meaningless.
-                let first_optional_h_idx = last_strict_h_idx + 1;
-
                 // Now, we fill in the neighbour indices.
 
                 // The first num_strict_cols + 1 entries are strict headers, including root.
                 // We do *not* put the optional headers in the structure.
-                for c in 0..=last_strict_h_idx {
+                for c in 0..last_strict_h_idx {
                         let l_idx = c;
                         let r_idx = l_idx + 1;
                         nodes[l_idx as usize].r = r_idx as NodeIdx;
@@ -463,92 +1128,669 @@ impl DancingLinkArray {
                 // Gives iterator over indices of normal nodes, not headers.
                 let normal_node_it = || (num_cols + 1)..n_all;
 
-                // Now each row of the nodes.
-                for r in 0..num_rows {
-                        let r = r as NodeIdx;
+                // Now we link everything in a single pass over the nodes.
+                // The nodes are sorted row-major, so each row is a consecutive run,
+                // and each column gets its nodes top to bottom, appended below the last one we saw.
+                let mut tails: Vec<NodeIdx> = (1..=num_cols as NodeIdx).collect();
+                let mut sizes: Vec<u64> = vec![0; num_cols];
+                // The first node of the current row, and that row.
+                let mut first_idx = INVALID_NODE_IDX;
+                let mut cur_row = None;
+                for i in normal_node_it() {
+                        let (row, col) = (nodes[i].row as usize, nodes[i].col as usize);
+                        let i = i as NodeIdx;
+
+                        if cur_row != Some (row) {
+                                debug_assert!(cur_row.is_none_or(|cur| cur < row) && row < num_rows);
+                                cur_row = Some (row);
+                                first_idx = i;
+                        }
+                        // Close the cycle of the row so far through this node.
+                        let last_idx = nodes[first_idx as usize].l;
+                        if i != first_idx {
+                                nodes[i as usize].l = last_idx;
+                                nodes[last_idx as usize].r = i;
+                        }
+                        nodes[i as usize].r = first_idx;
+                        nodes[first_idx as usize].l = i;
+
+                        let tail = tails[col];
+                        nodes[i as usize].u = tail;
+                        nodes[tail as usize].d = i;
+                        tails[col] = i;
+                        sizes[col] += 1;
+                }
 
-                        // We find the first node in the row.
-                        let mut idx_it = normal_node_it();
-                        let Some(first_idx) = idx_it.find(|&i| nodes[i].row == r) else {
-                                panic!("Empty row given");
+                // And close the cycle of each column through its header,
+                // which is a cycle of just the header for an empty column.
+                for (c, &tail) in tails.iter().enumerate() {
+                        let h_idx = c + 1;
+                        nodes[tail as usize].d = h_idx as NodeIdx;
+                        nodes[h_idx].u = tail;
+                }
+
+                debug_assert!(!colored || normal_node_it().all(|i| colors[i] == NO_COLOR || nodes[i].col as usize >= num_strict_cols));
+                let purifications: Vec<u32> = vec![0; num_cols];
+                let bounds: Vec<(u32, u32)> = vec![(1, 1); num_cols];
+                let counts: Vec<u32> = vec![0; num_cols];
+                let headers: Vec<NodeIdx> = (1..=num_cols as NodeIdx).collect();
+                let strict: Vec<bool> = (0..num_cols).map(|col| col < num_strict_cols).collect();
+
+                DancingLinkArray {
+                        nodes, sizes, headers, strict, purifications, bounds, counts, colors, colored,
+                        free: Vec::new(),
+                        num_rows,
+                        multiplicities: false,
+                        chooser: None,
+                        col_buf: Vec::new(),
+                        row_order: None,
+                        updates: 0,
+                        last_stats: SearchStats::default(),
+                        costs: None,
+                }
+        }
+
+        // A copy of the array in its current state, for another thread of a parallel search.
+        // Heuristics that cannot be forked are left at their defaults.
+        pub
+        fn fork (&self) -> DancingLinkArray
+        {
+                DancingLinkArray {
+                        nodes: self.nodes.clone(),
+                        free: self.free.clone(),
+                        colors: self.colors.clone(),
+                        colored: self.colored,
+                        num_rows: self.num_rows,
+                        headers: self.headers.clone(),
+                        strict: self.strict.clone(),
+                        sizes: self.sizes.clone(),
+                        purifications: self.purifications.clone(),
+                        bounds: self.bounds.clone(),
+                        counts: self.counts.clone(),
+                        multiplicities: self.multiplicities,
+                        chooser: self.chooser.as_ref().and_then(|chooser| chooser.fork()),
+                        col_buf: Vec::new(),
+                        row_order: self.row_order.as_ref().and_then(|row_order| row_order.fork()),
+                        updates: 0,
+                        last_stats: SearchStats::default(),
+                        costs: self.costs.clone(),
+                }
+        }
+
+        // Returns an index to a node in each row,
+        // such that array[r] is a NodeIndex to a node in row r, or INVALID_NODE_IDX if r was deleted.
+        // The returned array will remain valid, even after "removing a row".
+        pub
+        fn to_each_row (&self) -> Vec<NodeIdx>
+        {
+                let mut output: Vec<NodeIdx> = vec![INVALID_NODE_IDX; self.num_rows()];
+                for idx in self.it_over_node_idc() {
+                        let row = &mut output[self.get_row(idx) as usize];
+                        if *row == INVALID_NODE_IDX {
+                                *row = idx;
+                        }
+                }
+                output
+        }
+
+        // Adds a row with a node in each of the given columns, each with its color.
+        // Columns are numbered strict columns first, then optional ones,
+        // and strict columns cannot have a color.
+        // The row gets the next row index, and comes after every other row in its columns.
+        // Returns the node of the row in its first column.
+        // No rows may be removed while doing this.
+        pub
+        fn add_row (&mut self, cols: &[(usize, u32)]) -> NodeIdx
+        {
+                assert!(!cols.is_empty(), "A row needs at least one column");
+                let mut cols = cols.to_vec();
+                cols.sort_unstable_by_key(|&(col, _)| col);
+                assert!(cols.windows(2).all(|w| w[0].0 != w[1].0), "A row can have each column only once");
+                for &(col, color) in &cols {
+                        assert!(col < self.num_cols(), "Column {col} does not exist");
+                        assert!(color == NO_COLOR || !self.strict[col], "Strict columns cannot have a color");
+                }
+
+                if !self.colored && cols.iter().any(|&(_, color)| color != NO_COLOR) {
+                        // From now on, we keep track of the colors.
+                        self.colored = true;
+                        self.colors = vec![NO_COLOR; self.nodes.len()];
+                }
+
+                let row = self.num_rows as NodeIdx;
+                self.num_rows += 1;
+
+                let mut row_idc: Vec<NodeIdx> = Vec::with_capacity(cols.len());
+                for &(col, color) in &cols {
+                        let h_idx = self.headers[col];
+                        // At the bottom of the column, so the rows stay in the order of their indices.
+                        let node = Node {u: self.nodes[h_idx as usize].u, d: h_idx, l: 0, r: 0, row, col: col as NodeIdx};
+                        let n_idx = self.alloc_node(node, color);
+                        self.insert_node_ver(n_idx);
+                        *self.get_size_node_mut(n_idx) += 1;
+                        row_idc.push(n_idx);
+                }
+                for (i, &n_idx) in row_idc.iter().enumerate() {
+                        self.nodes[n_idx as usize].l = row_idc[(i + row_idc.len() - 1) % row_idc.len()];
+                        self.nodes[n_idx as usize].r = row_idc[(i + 1) % row_idc.len()];
+                }
+                row_idc[0]
+        }
+
+        // Reserves the next row index for a row without any nodes,
+        // which to_each_row then gives INVALID_NODE_IDX, as for a deleted row.
+        pub
+        fn add_empty_row (&mut self)
+        {
+                self.num_rows += 1;
+        }
+
+        // Takes a free slot in nodes for node, or makes a new one, and gives it color.
+        fn alloc_node (&mut self, node: Node, color: u32) -> NodeIdx
+        {
+                debug_assert!(self.colored || color == NO_COLOR);
+                if let Some(n_idx) = self.free.pop() {
+                        self.nodes[n_idx as usize] = node;
+                        if self.colored {
+                                self.colors[n_idx as usize] = color;
+                        }
+                        n_idx
+                } else {
+                        self.nodes.push(node);
+                        if self.colored {
+                                self.colors.push(color);
+                        }
+                        (self.nodes.len() - 1) as NodeIdx
+                }
+        }
+
+        // Adds a column with a node in the row of each of the given nodes.
+        // The column gets the next column index, after all existing columns,
+        // and a strict one has to be covered exactly once, like the others by default.
+        // No rows may be removed while doing this.
+        pub
+        fn add_column (&mut self, strict: bool, rows: &[NodeIdx]) -> usize
+        {
+                let col = self.num_cols();
+                let h_idx = self.alloc_node(Node {
+                        u: INVALID_NODE_IDX, d: INVALID_NODE_IDX, l: INVALID_NODE_IDX, r: INVALID_NODE_IDX,
+                        row: INVALID_NODE_IDX, col: col as NodeIdx,
+                }, NO_COLOR);
+                self.nodes[h_idx as usize].u = h_idx;
+                self.nodes[h_idx as usize].d = h_idx;
+                if strict {
+                        // Last in the horizontal structure, so choosers see the columns in index order.
+                        self.nodes[h_idx as usize].l = self.to_left(self.root());
+                        self.nodes[h_idx as usize].r = self.root();
+                        self.insert_node_hor(h_idx);
+                } else {
+                        self.nodes[h_idx as usize].l = h_idx;
+                        self.nodes[h_idx as usize].r = h_idx;
+                }
+                self.headers.push(h_idx);
+                self.strict.push(strict);
+                self.sizes.push(0);
+                self.purifications.push(0);
+                self.bounds.push((1, 1));
+                self.counts.push(0);
+
+                // In the order of the row indices, like every other column.
+                let mut rows = rows.to_vec();
+                rows.sort_unstable_by_key(|&n_idx| self.get_row(n_idx));
+                rows.dedup_by_key(|n_idx| self.get_row(*n_idx));
+                for n_idx in rows {
+                        let node = Node {
+                                u: self.nodes[h_idx as usize].u, d: h_idx, l: self.to_left(n_idx), r: n_idx,
+                                row: self.get_row(n_idx), col: col as NodeIdx,
                         };
+                        let new_idx = self.alloc_node(node, NO_COLOR);
+                        self.insert_node_ver(new_idx);
+                        self.insert_node_hor(new_idx);
+                        *self.get_size_node_mut(new_idx) += 1;
+                }
+                col
+        }
+
+        // Deletes the row of n_idx for good. Its nodes are reused by rows added later,
+        // but its row index is not.
+        // No rows may be removed while doing this.
+        pub
+        fn delete_row (&mut self, n_idx: NodeIdx)
+        {
+                let mut idx = n_idx;
+                loop {
+                        let next = self.to_right(idx);
+                        self.rm_node_ver(idx);
+                        *self.get_size_node_mut(idx) -= 1;
+                        self.nodes[idx as usize].row = INVALID_NODE_IDX;
+                        self.free.push(idx);
+                        if next == n_idx {
+                                break;
+                        }
+                        idx = next;
+                }
+        }
+}
+
+// Costs are spread over columns in fractions, which we round down after scaling by this.
+const COST_SCALE: u128 = 1 << 16;
+
+// The column chooser and row order of an array, so they can be swapped out together.
+pub
+struct Heuristics {
+        pub chooser:    Option<Box<dyn ColumnChooser>>,
+        pub row_order:  Option<Box<dyn RowOrder>>,
+}
+
+impl Heuristics {
+        // Breaks column ties and orders rows randomly.
+        // The same seed always gives the same choices.
+        pub
+        fn random (seed: u64) -> Heuristics
+        {
+                let mut rng = Rng::new(seed);
+                Heuristics {
+                        chooser: Some (Box::new(MrvRandom::new(rng.next_u64()))),
+                        row_order: Some (Box::new(Shuffle::new(rng.next_u64()))),
+                }
+        }
+}
+
+// One level of a resumable search:
+// the column we branched on, and the node in it whose row is currently chosen.
+// If node is the column header itself, we are in the branch that stops using the column.
+struct Choice {
+        col:    NodeIdx,
+        node:   NodeIdx,
+
+        // The number of rows of this column that we have ruled out,
+        // because their branches were already explored. See must_exclude.
+        excluded: usize,
+
+        // If the rows of the column were reordered, they are in the candidates buffer
+        // from the first index on, and the second index is the one to try next.
+        // Otherwise we simply follow the links.
+        cands: Option<(usize, usize)>,
+}
+
+// The choices of a search down to some depth, see Search::next_prefix.
+// Applying them to a fork of the array puts it in the state the search was in,
+// so the subtree below can be searched elsewhere.
+pub(crate)
+struct Prefix {
+        // The column and node of each choice, and the number of rows it ruled out.
+        choices:        Vec<(NodeIdx, NodeIdx, usize)>,
+
+        // The rows ruled out, in the order the search did so.
+        excluded:       Vec<NodeIdx>,
+}
+
+impl Prefix {
+        // The number of choices.
+        pub
+        fn depth (&self) -> usize
+        {
+                self.choices.len()
+        }
+
+        // Replays the choices, including the rows they ruled out.
+        // The array must be in the state the search started from.
+        pub
+        fn apply (&self, dla: &mut DancingLinkArray)
+        {
+                let mut excluded = self.excluded.iter();
+                for &(col, node, num_excluded) in &self.choices {
+                        for &row in excluded.by_ref().take(num_excluded) {
+                                dla.exclude_row(row);
+                        }
+                        dla.enter_branch(col, node);
+                }
+        }
+
+        // Takes back apply, in the opposite order.
+        pub
+        fn undo (&self, dla: &mut DancingLinkArray)
+        {
+                let mut excluded = self.excluded.iter().rev();
+                for &(col, node, num_excluded) in self.choices.iter().rev() {
+                        dla.leave_branch(col, node);
+                        for &row in excluded.by_ref().take(num_excluded) {
+                                dla.include_row(row);
+                        }
+                }
+        }
+
+        // The row indices chosen, as in Search::chosen_rows.
+        pub
+        fn chosen_rows <'a> (&'a self, dla: &'a DancingLinkArray) -> impl Iterator<Item = NodeIdx> + 'a
+        {
+                self.choices.iter()
+                        .filter(|(col, node, _)| node != col)
+                        .map(|&(_, node, _)| dla.get_row(node))
+        }
+}
+
+// What a single step of the search resulted in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub
+enum Step {
+        // The search made progress, but has not found anything yet.
+        Continue,
+        // The chosen rows form a solution.
+        Solution,
+        // There is nothing left to explore.
+        Exhausted,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SearchState {
+        // We have just chosen a row, and have to pick the next column.
+        Descend,
+        // The current choice is exhausted, or was reported as a solution.
+        Backtrack,
+        // The whole tree has been explored.
+        Done,
+}
+
+// A search through the array that can be suspended after every step.
+// Instead of living on the call stack, the chosen rows are kept in an explicit stack,
+// and the array is left with all of them removed between calls.
+// Therefore, the array must not be used for anything else until the search
+// is exhausted or reset.
+pub
+struct Search {
+        stack: Vec<Choice>,
+        state: SearchState,
+
+        // The rows ruled out by all choices on the stack, in order.
+        excluded: Vec<NodeIdx>,
+
+        // The reordered rows of all choices on the stack that have them, see Choice.
+        candidates: Vec<RowInfo>,
+
+        // What this search did so far.
+        stats: SearchStats,
+
+        // Scratch space to hand the chosen rows to the progress callback.
+        progress_buf: Vec<NodeIdx>,
+}
+
+impl Search {
+        pub
+        fn new () -> Search
+        {
+                Search {stack: Vec::new(), state: SearchState::Descend, excluded: Vec::new(), candidates: Vec::new(),
+                        stats: SearchStats::default(), progress_buf: Vec::new()}
+        }
+
+        // Does a single unit of work: either choosing a row, or taking back a choice.
+        // This lets the caller pause between steps, and inspect the partial solution.
+        pub
+        fn step (&mut self, dla: &mut DancingLinkArray) -> Step
+        {
+                self.step_budgeted(dla, None, None).expect("Without a budget, every step can be taken")
+        }
 
-                        // we use the state of idx_it.
+        // Like step, but returns None instead of making the choice that would
+        // bring the number of row choices over max_nodes.
+        // The search is then left as it was, so it can be resumed or reset.
+        // Also tells progress about the choice made, if it is time to.
+        pub(crate)
+        fn step_budgeted (&mut self, dla: &mut DancingLinkArray, max_nodes: Option<u64>, progress: Option<&mut Progress>) -> Option<Step>
+        {
+                let updates = dla.updates;
+                let step = self.step_uncounted(dla, max_nodes, progress);
+                if step.is_none() {
+                        // The step took back whatever it did, so none of it counts.
+                        dla.updates = updates;
+                }
+                self.stats.updates += dla.updates - updates;
+                if step == Some (Step::Solution) {
+                        self.stats.solutions += 1;
+                }
+                step
+        }
 
-                        // And for each next node in the row, we set it's left
-                        // neighbour to the last node we found,
-                        // and that node's right neighbour to this one.
-                        let mut last_idx = first_idx;
-                        for next_idx in idx_it {
-                                if nodes[next_idx].row != r {
-                                        continue;
+        fn step_uncounted (&mut self, dla: &mut DancingLinkArray, max_nodes: Option<u64>, progress: Option<&mut Progress>) -> Option<Step>
+        {
+                let out_of_nodes = max_nodes.is_some_and(|max| self.stats.nodes >= max);
+                Some (match self.state {
+                        SearchState::Descend => {
+                                let Some(c) = dla.choose_header() else {
+                                        // Every column is covered.
+                                        self.state = SearchState::Backtrack;
+                                        return Some (Step::Solution);
+                                };
+                                let cands_start = self.candidates.len();
+                                let (first, cands) = if dla.order_rows(c, &mut self.candidates) {
+                                        let first = self.candidates.get(cands_start).map_or(c, |row| row.node);
+                                        (first, Some((cands_start, cands_start + 1)))
+                                } else {
+                                        (dla.to_bottom(c), None)
+                                };
+                                if let Some(node) = dla.branch_from(c, first) {
+                                        if out_of_nodes {
+                                                // We pick a column again when resumed.
+                                                self.candidates.truncate(cands_start);
+                                                return None;
+                                        }
+                                        dla.enter_branch(c, node);
+                                        self.stack.push(Choice {col: c, node, excluded: 0, cands});
+                                        self.record_node(dla, progress);
+                                } else {
+                                        // Nothing fits this column, so this is a dead end.
+                                        self.candidates.truncate(cands_start);
+                                        self.state = SearchState::Backtrack;
+                                }
+                                Step::Continue
+                        }
+                        SearchState::Backtrack => {
+                                let Some(choice) = self.stack.last_mut() else {
+                                        self.state = SearchState::Done;
+                                        return Some (Step::Exhausted);
+                                };
+                                dla.leave_branch(choice.col, choice.node);
+                                let mut excluded = false;
+                                let next = if choice.node == choice.col {
+                                        // Not using the column was the last option.
+                                        None
+                                } else {
+                                        if dla.must_exclude(choice.col) {
+                                                dla.exclude_row(choice.node);
+                                                self.excluded.push(choice.node);
+                                                choice.excluded += 1;
+                                                excluded = true;
+                                        }
+                                        let next_cand = match &mut choice.cands {
+                                                Some((_, next)) => {
+                                                        let cand = self.candidates.get(*next).map_or(choice.col, |row| row.node);
+                                                        *next += 1;
+                                                        cand
+                                                }
+                                                None => dla.to_bottom(choice.node),
+                                        };
+                                        dla.branch_from(choice.col, next_cand)
+                                };
+                                if let Some(node) = next {
+                                        if out_of_nodes {
+                                                // Take back what we did, and stay in the branch we were in.
+                                                if let Some((_, next)) = &mut choice.cands {
+                                                        *next -= 1;
+                                                }
+                                                if excluded {
+                                                        self.excluded.pop();
+                                                        choice.excluded -= 1;
+                                                        dla.include_row(choice.node);
+                                                }
+                                                dla.enter_branch(choice.col, choice.node);
+                                                return None;
+                                        }
+                                        choice.node = node;
+                                        dla.enter_branch(choice.col, node);
+                                        self.state = SearchState::Descend;
+                                        self.record_node(dla, progress);
+                                } else {
+                                        let num_excluded = choice.excluded;
+                                        if let Some((start, _)) = choice.cands {
+                                                self.candidates.truncate(start);
+                                        }
+                                        self.stack.pop();
+                                        self.include_excluded(dla, num_excluded);
                                 }
-                                nodes[next_idx].l = last_idx as NodeIdx;
-                                nodes[last_idx].r = next_idx as NodeIdx;
-                                last_idx = next_idx;
+                                Step::Continue
                         }
+                        SearchState::Done => Step::Exhausted,
+                })
+        }
 
-                        // cycle
-                        nodes[last_idx].r = first_idx as NodeIdx;
-                        nodes[first_idx].l = last_idx as NodeIdx;
+        // Advances the search to the next solution.
+        // Returns false if there are no more solutions,
+        // in which case the array is back in the state we started from.
+        pub
+        fn next_solution (&mut self, dla: &mut DancingLinkArray) -> bool
+        {
+                loop {
+                        match self.step(dla) {
+                                Step::Continue  => {}
+                                Step::Solution  => return true,
+                                Step::Exhausted => return false,
+                        }
                 }
+        }
 
-                // And columns are almost the same.
-                for c in 0..num_cols {
-                        let c = c as NodeIdx;
+        // Like next_solution, but gives up once a limit is exceeded, returning None.
+        // The array then still has the chosen rows removed, until the search is reset.
+        // The search stops right before the row choice that would go over the node budget,
+        // so it never makes more than that many.
+        pub
+        fn next_solution_limited (&mut self, dla: &mut DancingLinkArray, limits: &Limits, progress: Option<&mut Progress>) -> Option<bool>
+        {
+                self.next_solution_pruned(dla, limits, |_, _| false, progress)
+        }
 
-                        // We find the first node in the col.
-                        let mut idx_it = normal_node_it();
-                        let Some(first_idx) = idx_it.find(|&i| nodes[i].col == c) else {
-                                panic!("Empty column given");
-                        };
+        // Like next_solution_limited, but skips the subtree below every state
+        // for which prune returns true, before choosing the next column.
+        pub
+        fn next_solution_pruned <P> (&mut self, dla: &mut DancingLinkArray, limits: &Limits, mut prune: P, mut progress: Option<&mut Progress>) -> Option<bool>
+        where P: FnMut(&DancingLinkArray, &Search) -> bool
+        {
+                // How many steps we take between looking at the clock.
+                const CLOCK_INTERVAL: u32 = 1024;
 
-                        let mut last_idx = first_idx;
-                        for next_idx in idx_it {
-                                if nodes[next_idx].col != c {
-                                        continue;
+                let mut steps: u32 = 0;
+                loop {
+                        if limits.exceeded(steps.is_multiple_of(CLOCK_INTERVAL)) {
+                                return None;
+                        }
+                        steps = steps.wrapping_add(1);
+                        if self.state == SearchState::Descend && prune(dla, self) {
+                                self.state = SearchState::Backtrack;
+                                continue;
+                        }
+                        match self.step_budgeted(dla, limits.max_nodes, progress.as_deref_mut())? {
+                                Step::Continue  => {}
+                                Step::Solution  => return Some (true),
+                                Step::Exhausted => return Some (false),
+                        }
+                }
+        }
+
+        // Advances the search to the next state with depth choices made,
+        // or to the next solution with fewer choices. That state is then given by prefix.
+        // Returns false if there are no more such states,
+        // in which case the array is back in the state we started from.
+        pub
+        fn next_prefix (&mut self, dla: &mut DancingLinkArray, depth: usize) -> bool
+        {
+                loop {
+                        if self.state == SearchState::Descend && self.stack.len() >= depth {
+                                // Do not go deeper, but carry on with the next branch.
+                                self.state = SearchState::Backtrack;
+                                return true;
+                        }
+                        match self.step(dla) {
+                                Step::Continue  => {}
+                                Step::Solution  => {
+                                        // Whoever searches below the prefix finds this solution again,
+                                        // so it is counted there.
+                                        self.stats.solutions -= 1;
+                                        return true;
                                 }
-                                nodes[next_idx].u = last_idx as NodeIdx;
-                                nodes[last_idx].d = next_idx as NodeIdx;
-                                last_idx = next_idx;
+                                Step::Exhausted => return false,
                         }
+                }
+        }
 
-                        // cycle with headers between.
-                        let h_idx = (c + 1) as usize;
-                        nodes[last_idx].d = h_idx as NodeIdx;
-                        nodes[first_idx].u = h_idx as NodeIdx;
-                        nodes[h_idx].d = first_idx as NodeIdx;
-                        nodes[h_idx].u = last_idx as NodeIdx;
+        // The choices the search currently made.
+        pub(crate)
+        fn prefix (&self) -> Prefix
+        {
+                Prefix {
+                        choices: self.stack.iter().map(|choice| (choice.col, choice.node, choice.excluded)).collect(),
+                        excluded: self.excluded.clone(),
                 }
+        }
 
-                // And finally, we fill in the number of nodes in each column.
-                let mut sizes: Box<[u64]> = std::iter::repeat_n(0, num_cols).collect();
-                for i in normal_node_it() {
-                        let col = nodes[i].col;
-                        sizes[col as usize] += 1;
+        // What the search did so far.
+        pub
+        fn stats (&self) -> &SearchStats
+        {
+                &self.stats
+        }
+
+        // Takes the statistics out of the search, leaving empty ones behind.
+        pub
+        fn take_stats (&mut self) -> SearchStats
+        {
+                std::mem::take(&mut self.stats)
+        }
+
+        // Counts the choice that was just pushed, and reports progress if it is time to.
+        fn record_node (&mut self, dla: &DancingLinkArray, progress: Option<&mut Progress>)
+        {
+                self.stats.record_node(self.stack.len() - 1);
+                let Some(progress) = progress else {
+                        return;
+                };
+                if self.stats.nodes.is_multiple_of(progress.every) {
+                        self.progress_buf.clear();
+                        self.progress_buf.extend(self.stack.iter()
+                                .filter(|choice| choice.node != choice.col)
+                                .map(|choice| dla.nodes[choice.node as usize].row));
+                        (progress.callback)(self.stack.len(), &self.progress_buf);
                 }
+        }
 
-                DancingLinkArray {nodes, sizes, first_optional_h_idx, num_headers}
+        // The row indices currently chosen, in the order they were chosen.
+        // Right after a solution was found, these form that solution.
+        pub
+        fn chosen_rows <'a> (&'a self, dla: &'a DancingLinkArray) -> impl Iterator<Item = NodeIdx> + 'a
+        {
+                self.stack.iter()
+                        .filter(|choice| choice.node != choice.col)
+                        .map(|choice| dla.get_row(choice.node))
         }
 
-        // Returns an index to a node in each row,
-        // such that array[r] is a NodeIndex to a node in row r.
-        // The returned array will remain valid, even after "removing a row".
+        // Abandons the search, and puts back every row it removed.
         pub
-        fn to_each_row (&self) -> Box<[NodeIdx]>
+        fn reset (&mut self, dla: &mut DancingLinkArray)
         {
-                // By construction, Each row should have at least one node.
-                let n_rows = self.num_rows();
-                let mut output: Vec<NodeIdx> = Vec::with_capacity(n_rows);
+                let updates = dla.updates;
+                while let Some(choice) = self.stack.pop() {
+                        dla.leave_branch(choice.col, choice.node);
+                        self.include_excluded(dla, choice.excluded);
+                }
+                self.stats.updates += dla.updates - updates;
+                self.candidates.clear();
+                self.state = SearchState::Descend;
+        }
 
-                let mut next_row = 0;
-                for idx in self.it_over_node_idc() {
-                        if self.get_row(idx) == next_row {
-                                output.push(idx);
-                                next_row += 1;
-                        }
+        // Puts back the last n rows that were ruled out, in the opposite order.
+        fn include_excluded (&mut self, dla: &mut DancingLinkArray, n: usize)
+        {
+                for _ in 0..n {
+                        let node = self.excluded.pop().expect("Excluded rows out of sync");
+                        dla.include_row(node);
                 }
-                debug_assert_eq!(output.len(), n_rows);
-                output.into_boxed_slice()
         }
 }
@@ -0,0 +1,69 @@
+use std::fmt;
+
+// What went wrong in one of the try_ methods of UCSolver.
+// Rows and columns are given by their index, in the order in which they were given,
+// with strict columns before optional ones.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub
+enum DlxError {
+        // A row covers no column.
+        EmptyRow {row: usize},
+        // A row covers a column that does not exist.
+        ColumnOutOfRange {row: usize, col: usize},
+        // A row covers a column value that is not among the columns.
+        UnknownColumn {row: usize},
+        // Column col has the same value as an earlier column.
+        DuplicateColumn {col: usize},
+        // A row covers a column more than once.
+        DuplicateEntry {row: usize, col: usize},
+        // A row gives a strict column a color.
+        ColoredStrictColumn {row: usize, col: usize},
+        // A strict column was given bounds with lo > hi, or hi = 0.
+        InvalidBounds {col: usize, lo: u32, hi: u32},
+        // The number of bounds is not the number of strict columns.
+        WrongNumberOfBounds {expected: usize, given: usize},
+        // No row, that was not deleted, has the given data.
+        UnknownRow,
+        // A row kept under EmptyRows::Ignore was given to add_column.
+        EmptyRowInColumn {row: usize},
+        // A row with the given data already exists.
+        RowExists,
+        // The row was already removed with set_state.
+        RowAlreadyRemoved,
+        // The row shares a column with a row removed with set_state,
+        // so they can not be in a solution together.
+        RowConflicts,
+        // The solver can not be changed while rows are removed with set_state.
+        RowsRemoved,
+        // More changes were to be recovered than were made.
+        NothingToRecover {requested: usize, removed: usize},
+}
+
+impl fmt::Display for DlxError {
+        fn fmt (&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+        {
+                match self {
+                        DlxError::EmptyRow {row}                 => write!(f, "Row {row} covers no column"),
+                        DlxError::ColumnOutOfRange {row, col}    => write!(f, "Row {row} covers column {col}, which does not exist"),
+                        DlxError::UnknownColumn {row}            => write!(f, "Row {row} covers a column that was not given"),
+                        DlxError::DuplicateColumn {col}          => write!(f, "Column {col} has the same value as an earlier column"),
+                        DlxError::DuplicateEntry {row, col}      => write!(f, "Row {row} covers column {col} more than once"),
+                        DlxError::ColoredStrictColumn {row, col} => write!(f, "Row {row} gives strict column {col} a color"),
+                        DlxError::InvalidBounds {col, lo, hi}    => write!(f, "Column {col} has invalid bounds {lo}..={hi}"),
+                        DlxError::WrongNumberOfBounds {expected, given} => {
+                                write!(f, "Got {given} bounds for {expected} strict columns")
+                        }
+                        DlxError::UnknownRow                     => write!(f, "Tried to use a non-existent row"),
+                        DlxError::EmptyRowInColumn {row}         => write!(f, "Tried to add ignored empty row {row} to a column"),
+                        DlxError::RowExists                      => write!(f, "Tried to add an already existing row"),
+                        DlxError::RowAlreadyRemoved              => write!(f, "Tried to remove an already removed row"),
+                        DlxError::RowConflicts                   => write!(f, "Tried to remove a row that conflicts with a removed row"),
+                        DlxError::RowsRemoved                    => write!(f, "Tried to change the solver while rows are removed"),
+                        DlxError::NothingToRecover {requested, removed} => {
+                                write!(f, "Tried to recover {requested} changes, but only {removed} were made")
+                        }
+                }
+        }
+}
+
+impl std::error::Error for DlxError {}
@@ -0,0 +1,274 @@
+// Reading and writing instances in the text format of Knuth's DLX1, DLX2 and DLX3 programs.
+//
+// The first line lists the items (columns). Primary items come first, then a lone `|`,
+// then the secondary ones. A primary item can be preceded by `u:v|` or `v|` to make it
+// be covered between u and v times, or exactly v times.
+// Every following line is an option (row): the names of its items,
+// where secondary items can be given a color with a `:color` suffix.
+// Empty lines, and lines that start with `|`, are comments.
+// An item does not have to be in any option, but a primary one then leaves no solution.
+
+use super::{dla, UCSolver};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+// The item and color names of an instance that was read, to write it back with the same names.
+#[derive(Clone, Debug)]
+pub(super)
+struct Names {
+        items:  Vec<String>,
+        // The names of the colors, by number. Colors added after the instance was read get named when they are used.
+        colors: HashMap<u32, String>,
+}
+
+// What is wrong with a text in Knuth's format, and on which line, counting from 1.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub
+struct ParseError {
+        pub line:       usize,
+        pub message:    String,
+}
+
+impl fmt::Display for ParseError {
+        fn fmt (&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+        {
+                write!(f, "line {}: {}", self.line, self.message)
+        }
+}
+
+impl std::error::Error for ParseError {}
+
+fn error <T> (line: usize, message: String) -> Result<T, ParseError>
+{
+        Err (ParseError {line, message})
+}
+
+fn valid_name (name: &str) -> bool
+{
+        !name.is_empty() && !name.contains([':', '|'])
+}
+
+// Parses the `u:v` or `v` in front of a primary item.
+fn parse_bounds (bounds: &str) -> Option<(u32, u32)>
+{
+        let (lo, hi) = match bounds.split_once(':') {
+                Some ((lo, hi)) => (lo.parse().ok()?, hi.parse().ok()?),
+                None            => {
+                        let v = bounds.parse().ok()?;
+                        (v, v)
+                }
+        };
+        (lo <= hi && hi > 0).then_some((lo, hi))
+}
+
+impl UCSolver <String> {
+        // Reads an instance in Knuth's format, see the top of this file.
+        // Each row is the text of its option, with the items separated by single spaces.
+        // Options may be given more than once, but then set_state and the like
+        // can only refer to the first of them.
+        // Colors are only compared with each other, so any names will do.
+        pub
+        fn from_dlx_format (text: &str) -> Result<UCSolver <String>, ParseError>
+        {
+                let mut lines = text.lines().enumerate()
+                        .map(|(i, line)| (i + 1, line.trim()))
+                        .filter(|(_, line)| !line.is_empty() && !line.starts_with('|'));
+
+                let Some((item_line, items)) = lines.next() else {
+                        return error(1, "Missing the line of items".to_string());
+                };
+
+                let mut strict: Vec<(String, (u32, u32))> = Vec::new();
+                let mut opt: Vec<String> = Vec::new();
+                let mut seen_bar = false;
+                for tok in items.split_whitespace() {
+                        if tok == "|" {
+                                if seen_bar {
+                                        return error(item_line, "More than one | between items".to_string());
+                                }
+                                seen_bar = true;
+                                continue;
+                        }
+                        let (bounds, name) = match tok.split_once('|') {
+                                Some ((bounds, name)) => {
+                                        if seen_bar {
+                                                return error(item_line, format!("Secondary item {name} cannot have bounds"));
+                                        }
+                                        let Some(bounds) = parse_bounds(bounds) else {
+                                                return error(item_line, format!("Invalid bounds {bounds} of item {name}"));
+                                        };
+                                        (bounds, name)
+                                }
+                                None => ((1, 1), tok),
+                        };
+                        if !valid_name(name) {
+                                return error(item_line, format!("Invalid item name {name:?}"));
+                        }
+                        if seen_bar {
+                                opt.push(name.to_string());
+                        } else {
+                                strict.push((name.to_string(), bounds));
+                        }
+                }
+                if strict.is_empty() {
+                        return error(item_line, "There has to be a primary item".to_string());
+                }
+
+                let item_names: Vec<String> = strict.iter().map(|(name, _)| name.clone()).chain(opt).collect();
+                let mut cols: HashMap<&str, usize> = HashMap::new();
+                for (col, name) in item_names.iter().enumerate() {
+                        if cols.insert(name, col).is_some() {
+                                return error(item_line, format!("Item {name} is given twice"));
+                        }
+                }
+
+                let mut colors: HashMap<String, u32> = HashMap::new();
+                let mut color_names: Vec<String> = Vec::new();
+                let mut rows: Vec<String> = Vec::new();
+                let mut elems: Vec<(usize, usize, u32)> = Vec::new();
+                for (line, option) in lines {
+                        let row = rows.len();
+                        let mut row_elems: Vec<(usize, usize, u32)> = Vec::new();
+                        for tok in option.split_whitespace() {
+                                let (name, color) = match tok.split_once(':') {
+                                        Some ((name, color)) => (name, Some (color)),
+                                        None                 => (tok, None),
+                                };
+                                let Some(&col) = cols.get(name) else {
+                                        return error(line, format!("Unknown item {name}"));
+                                };
+                                let color = match color {
+                                        None => 0,
+                                        Some (_) if col < strict.len() => {
+                                                return error(line, format!("Primary item {name} cannot have a color"));
+                                        }
+                                        Some (color) if !valid_name(color) => {
+                                                return error(line, format!("Invalid color name {color:?}"));
+                                        }
+                                        Some (color) => *colors.entry(color.to_string()).or_insert_with(|| {
+                                                color_names.push(color.to_string());
+                                                color_names.len() as u32
+                                        }),
+                                };
+                                row_elems.push((row, col, color));
+                        }
+                        row_elems.sort_unstable();
+                        if row_elems.windows(2).any(|w| w[0].1 == w[1].1) {
+                                return error(line, "An option has an item more than once".to_string());
+                        }
+                        let text = option.split_whitespace().collect::<Vec<_>>().join(" ");
+                        elems.extend(row_elems);
+                        rows.push(text);
+                }
+
+                let num_rows = rows.len();
+                let mut array = dla::DancingLinkArray::from_sorted_colored_idc_unsafe(elems, num_rows, strict.len(), item_names.len() - strict.len());
+                if strict.iter().any(|&(_, bounds)| bounds != (1, 1)) {
+                        let bounds: Vec<(u32, u32)> = strict.iter().map(|&(_, bounds)| bounds).collect();
+                        array.set_bounds(&bounds);
+                }
+                let mut solver = Self::from_array(array, rows);
+                let colors = (1..).zip(color_names).collect();
+                solver.names = Some (Names {items: item_names, colors});
+                Ok (solver)
+        }
+}
+
+impl <R: Clone + Eq> UCSolver <R> {
+        // Writes the solver in Knuth's format, see the top of this file.
+        // This and the other exports, to_cnf and write_lp, write the whole problem:
+        // the solver as it is without any rows removed by set_state, and without deleted rows.
+        // If the solver was read from this format, its item and color names are used,
+        // and otherwise the items are called c0, c1, ... after their index, and the colors by number.
+        // The format needs a primary item, so a solver without strict columns
+        // gives an error of kind InvalidInput, and nothing is written.
+        pub
+        fn write_dlx_format <W: Write> (&self, out: &mut W) -> io::Result<()>
+        {
+                let num_cols = self.array.num_cols();
+                if !(0..num_cols).any(|col| self.array.is_strict(col)) {
+                        return Err (io::Error::new(io::ErrorKind::InvalidInput, "There has to be a strict column"));
+                }
+                let item_name = |col: usize| match &self.names {
+                        Some (names) => names.items[col].clone(),
+                        None         => format!("c{col}"),
+                };
+                let color_name = |color: u32| match &self.names {
+                        Some (names) => names.colors[&color].clone(),
+                        None         => color.to_string(),
+                };
+
+                let mut header: Vec<String> = Vec::new();
+                for col in (0..num_cols).filter(|&col| self.array.is_strict(col)) {
+                        let name = item_name(col);
+                        header.push(match self.array.col_bounds(col) {
+                                (1, 1)   => name,
+                                (lo, hi) => format!("{lo}:{hi}|{name}"),
+                        });
+                }
+                let opt: Vec<String> = (0..num_cols).filter(|&col| !self.array.is_strict(col)).map(item_name).collect();
+                if !opt.is_empty() {
+                        header.push("|".to_string());
+                        header.extend(opt);
+                }
+                writeln!(out, "{}", header.join(" "))?;
+
+                for node in (0..self.to_rows.len()).filter_map(|r_idx| self.row_node(r_idx)) {
+                        let option: Vec<String> = self.array.row_cols(node).into_iter().map(|(col, color)| {
+                                if color == 0 {
+                                        item_name(col)
+                                } else {
+                                        format!("{}:{}", item_name(col), color_name(color))
+                                }
+                        }).collect();
+                        writeln!(out, "{}", option.join(" "))?;
+                }
+                Ok (())
+        }
+
+        // Like write_dlx_format, but to a string, with the same error if there is no strict column.
+        pub
+        fn to_dlx_format (&self) -> io::Result<String>
+        {
+                let mut out = Vec::new();
+                self.write_dlx_format(&mut out)?;
+                Ok (String::from_utf8(out).expect("Names are valid UTF-8"))
+        }
+}
+
+// A name for column col, added after the instance was read, that no other item has.
+pub(super)
+fn fresh_item_name (names: &Names, col: usize) -> String
+{
+        let mut name = format!("c{col}");
+        while names.items.contains(&name) {
+                name.push('\'');
+        }
+        name
+}
+
+// Names the colors of a row added after the instance was read that have no name yet,
+// after their number, if no other color has that name.
+pub(super)
+fn name_colors (names: &mut Names, colors: impl IntoIterator<Item = u32>)
+{
+        for color in colors {
+                if color == 0 || names.colors.contains_key(&color) {
+                        continue;
+                }
+                let mut name = color.to_string();
+                while names.colors.values().any(|other| *other == name) {
+                        name.push('\'');
+                }
+                names.colors.insert(color, name);
+        }
+}
+
+impl Names {
+        pub(super)
+        fn push_item (&mut self, name: String)
+        {
+                self.items.push(name);
+        }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// Bounds on how much work a search may do before it gives up.
+// The default has no bounds at all.
+#[derive(Clone, Default, Debug)]
+pub
+struct Limits {
+        // The maximum number of row choices, counting every branch that is tried.
+        // The search stops before the choice that would go over it.
+        pub max_nodes:  Option<u64>,
+
+        // The moment after which the search stops.
+        pub deadline:   Option<Instant>,
+
+        // The search stops once this is set to true, e.g. from another thread.
+        pub cancel:     Option<Arc<AtomicBool>>,
+}
+
+impl Limits {
+        pub
+        fn new () -> Limits
+        {
+                Limits::default()
+        }
+
+        pub
+        fn with_max_nodes (mut self, max_nodes: u64) -> Limits
+        {
+                self.max_nodes = Some (max_nodes);
+                self
+        }
+
+        pub
+        fn with_deadline (mut self, deadline: Instant) -> Limits
+        {
+                self.deadline = Some (deadline);
+                self
+        }
+
+        // Sets the deadline to the given time from now.
+        pub
+        fn with_timeout (self, timeout: Duration) -> Limits
+        {
+                self.with_deadline(Instant::now() + timeout)
+        }
+
+        pub
+        fn with_cancel (mut self, cancel: Arc<AtomicBool>) -> Limits
+        {
+                self.cancel = Some (cancel);
+                self
+        }
+
+        // Whether a search has to stop because it was cancelled or ran out of time.
+        // The node budget is up to the search itself, see Search::step_budgeted.
+        // Reading the clock is slow compared to a search step,
+        // so the deadline is only looked at when check_clock is set.
+        pub(crate)
+        fn exceeded (&self, check_clock: bool) -> bool
+        {
+                self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+                        || (check_clock && self.deadline.is_some_and(|deadline| Instant::now() >= deadline))
+        }
+}
+
+// The result of a search under limits.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub
+enum Outcome <T> {
+        // The search found what it was asked for.
+        Solved (T),
+        // The search ran to the end, and there is no solution.
+        Unsatisfiable,
+        // A limit was hit before the search could finish, so we know nothing.
+        Aborted,
+}
+
+impl <T> Outcome <T> {
+        // Converts the result of a search that cannot be aborted.
+        pub(crate)
+        fn unlimited (self) -> Option<T>
+        {
+                match self {
+                        Outcome::Solved(t)      => Some (t),
+                        Outcome::Unsatisfiable  => None,
+                        Outcome::Aborted        => unreachable!("Search without limits was aborted"),
+                }
+        }
+
+        pub
+        fn map <U, F: FnOnce(T) -> U> (self, f: F) -> Outcome <U>
+        {
+                match self {
+                        Outcome::Solved(t)      => Outcome::Solved(f(t)),
+                        Outcome::Unsatisfiable  => Outcome::Unsatisfiable,
+                        Outcome::Aborted        => Outcome::Aborted,
+                }
+        }
+}
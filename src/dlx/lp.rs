@@ -0,0 +1,242 @@
+// Writing instances as integer programs, in CPLEX LP and in free MPS format,
+// to compare with MILP solvers.
+//
+// Every row becomes a binary variable x<i>, which is 1 when the row is chosen.
+// A strict column c<j> becomes the constraint that the sum of its rows lies within its bounds,
+// which is an equality for the usual exact cover, and an optional column is at most 1.
+// In a colored optional column, all rows with the same color k switch on one binary y<j>_<k>,
+// and at most one of those and the uncolored rows may be 1.
+// Rows that the search never chooses, see to_cnf, are fixed to 0.
+// Without any variables, e.g. when all rows are deleted, a variable z fixed to 0 stands in,
+// so that the objective and the constraints still have a sum.
+// The objective is to minimise the total cost of the chosen rows.
+
+use super::cnf::exclusive_vars;
+use super::UCSolver;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Sense {
+        Eq,
+        Le,
+        Ge,
+}
+
+struct Constraint {
+        name:   String,
+        // Pairs of a variable and its coefficient.
+        terms:  Vec<(usize, i64)>,
+        sense:  Sense,
+        rhs:    i64,
+        // For Ge, the constraint also has the sum be at most rhs + range.
+        range:  Option<i64>,
+}
+
+struct Model {
+        names:          Vec<String>,
+        costs:          Vec<f64>,
+        fixed_zero:     Vec<bool>,
+        constraints:    Vec<Constraint>,
+}
+
+impl Model {
+        fn new_var (&mut self, name: String, cost: f64) -> usize
+        {
+                self.names.push(name);
+                self.costs.push(cost);
+                self.fixed_zero.push(false);
+                self.names.len() - 1
+        }
+}
+
+impl <R: Clone + Eq> UCSolver <R> {
+        // Builds the model, see the top of this file, and the rows of the x variables in order.
+        fn to_model <F: Fn(&R) -> f64> (&self, cost: F) -> (Model, Vec<R>)
+        {
+                let mut model = Model {names: Vec::new(), costs: Vec::new(), fixed_zero: Vec::new(), constraints: Vec::new()};
+                let (rows, members) = self.column_members(|r, choosable| {
+                        let var = model.new_var(format!("x{}", model.names.len()), cost(r));
+                        model.fixed_zero[var] = !choosable;
+                        var
+                });
+
+                for (col, members) in members.iter().enumerate() {
+                        let name = format!("c{col}");
+                        if self.array.is_strict(col) {
+                                let terms = members.iter().map(|&(var, _)| (var, 1)).collect();
+                                let (lo, hi) = self.array.col_bounds(col);
+                                let (lo, hi) = (i64::from(lo), i64::from(hi));
+                                let (sense, rhs, range) = match (lo, hi) {
+                                        _ if lo == hi   => (Sense::Eq, lo, None),
+                                        (0, _)          => (Sense::Le, hi, None),
+                                        _               => (Sense::Ge, lo, Some (hi - lo)),
+                                };
+                                model.constraints.push(Constraint {name, terms, sense, rhs, range});
+                                continue;
+                        }
+                        let new_shared = |model: &mut Model, color| model.new_var(format!("y{col}_{color}"), 0.0);
+                        let imply = |model: &mut Model, var, shared| model.constraints.push(Constraint {
+                                name:   format!("c{col}_{}", model.names[var]),
+                                terms:  vec![(var, 1), (shared, -1)],
+                                sense:  Sense::Le,
+                                rhs:    0,
+                                range:  None,
+                        });
+                        let terms = exclusive_vars(&mut model, members, new_shared, imply).into_iter().map(|var| (var, 1)).collect();
+                        model.constraints.push(Constraint {name, terms, sense: Sense::Le, rhs: 1, range: None});
+                }
+                if model.names.is_empty() {
+                        let var = model.new_var("z".to_string(), 0.0);
+                        model.fixed_zero[var] = true;
+                }
+                (model, rows)
+        }
+
+        // Writes the solver in CPLEX LP format, see the top of this file,
+        // with the same rows as write_dlx_format.
+        // cost gives the objective coefficient of each row.
+        // Returns the rows in the order of their variables: x<i> is row i of the result.
+        // Rows fixed to 0 are left out of Binaries, as some readers reset the bounds of binaries to [0, 1].
+        pub
+        fn write_lp <W: Write, F: Fn(&R) -> f64> (&self, out: &mut W, cost: F) -> io::Result<Vec<R>>
+        {
+                // LP readers limit the length of a line, so long sums are wrapped.
+                const TERMS_PER_LINE: usize = 8;
+
+                fn write_sum <W: Write> (out: &mut W, names: &[String], terms: impl Iterator<Item = (usize, f64)>) -> io::Result<()>
+                {
+                        let mut empty = true;
+                        for (i, (var, coef)) in terms.enumerate() {
+                                if i > 0 && i.is_multiple_of(TERMS_PER_LINE) {
+                                        write!(out, "\n   ")?;
+                                }
+                                let sign = if coef < 0.0 {'-'} else {'+'};
+                                write!(out, " {sign} {} {}", coef.abs(), names[var])?;
+                                empty = false;
+                        }
+                        // An empty column still needs a sum, for its constraint to be read.
+                        if empty {
+                                write!(out, " 0 {}", names[0])?;
+                        }
+                        Ok (())
+                }
+
+                let (model, rows) = self.to_model(cost);
+                let names = &model.names;
+                writeln!(out, "\\ The {} rows are x0 onwards.", rows.len())?;
+                writeln!(out, "Minimize")?;
+                write!(out, " obj:")?;
+                write_sum(out, names, model.costs.iter().copied().enumerate())?;
+                writeln!(out)?;
+
+                writeln!(out, "Subject To")?;
+                for con in &model.constraints {
+                        let terms = || con.terms.iter().map(|&(var, coef)| (var, coef as f64));
+                        let op = match con.sense {
+                                Sense::Eq => "=",
+                                Sense::Le => "<=",
+                                Sense::Ge => ">=",
+                        };
+                        write!(out, " {}:", con.name)?;
+                        write_sum(out, names, terms())?;
+                        writeln!(out, " {op} {}", con.rhs)?;
+                        if let Some(range) = con.range {
+                                write!(out, " {}_hi:", con.name)?;
+                                write_sum(out, names, terms())?;
+                                writeln!(out, " <= {}", con.rhs + range)?;
+                        }
+                }
+
+                if model.fixed_zero.iter().any(|&fixed| fixed) {
+                        writeln!(out, "Bounds")?;
+                        for (var, _) in model.fixed_zero.iter().enumerate().filter(|&(_, &fixed)| fixed) {
+                                writeln!(out, " {} = 0", names[var])?;
+                        }
+                }
+                if model.fixed_zero.iter().any(|&fixed| !fixed) {
+                        writeln!(out, "Binaries")?;
+                        for (var, _) in model.fixed_zero.iter().enumerate().filter(|&(_, &fixed)| !fixed) {
+                                writeln!(out, " {}", names[var])?;
+                        }
+                }
+                writeln!(out, "End")?;
+                Ok (rows)
+        }
+
+        // Like write_lp, but in free MPS format.
+        // Rows fixed to 0 get an FX bound instead of BV.
+        pub
+        fn write_mps <W: Write, F: Fn(&R) -> f64> (&self, out: &mut W, cost: F) -> io::Result<Vec<R>>
+        {
+                let (model, rows) = self.to_model(cost);
+                let names = &model.names;
+                writeln!(out, "* The {} rows are x0 onwards.", rows.len())?;
+                writeln!(out, "NAME dlx")?;
+                writeln!(out, "ROWS")?;
+                writeln!(out, " N obj")?;
+                // The entries of each variable, as MPS lists them by variable.
+                let mut entries: Vec<Vec<(&str, i64)>> = vec![Vec::new(); names.len()];
+                for con in &model.constraints {
+                        let sense = match con.sense {
+                                Sense::Eq => 'E',
+                                Sense::Le => 'L',
+                                Sense::Ge => 'G',
+                        };
+                        writeln!(out, " {sense} {}", con.name)?;
+                        for &(var, coef) in &con.terms {
+                                entries[var].push((&con.name, coef));
+                        }
+                }
+
+                writeln!(out, "COLUMNS")?;
+                writeln!(out, "    MARKER 'MARKER' 'INTORG'")?;
+                for (var, name) in names.iter().enumerate() {
+                        writeln!(out, "    {name} obj {}", model.costs[var])?;
+                        for &(con, coef) in &entries[var] {
+                                writeln!(out, "    {name} {con} {coef}")?;
+                        }
+                }
+                writeln!(out, "    MARKER 'MARKER' 'INTEND'")?;
+
+                writeln!(out, "RHS")?;
+                for con in model.constraints.iter().filter(|con| con.rhs != 0) {
+                        writeln!(out, "    RHS {} {}", con.name, con.rhs)?;
+                }
+                if model.constraints.iter().any(|con| con.range.is_some()) {
+                        writeln!(out, "RANGES")?;
+                        for con in &model.constraints {
+                                if let Some(range) = con.range {
+                                        writeln!(out, "    RNG {} {range}", con.name)?;
+                                }
+                        }
+                }
+                writeln!(out, "BOUNDS")?;
+                for (var, name) in names.iter().enumerate() {
+                        if model.fixed_zero[var] {
+                                writeln!(out, " FX BND {name} 0")?;
+                        } else {
+                                writeln!(out, " BV BND {name}")?;
+                        }
+                }
+                writeln!(out, "ENDATA")?;
+                Ok (rows)
+        }
+
+        // Like write_lp, but to a string.
+        pub
+        fn to_lp <F: Fn(&R) -> f64> (&self, cost: F) -> (String, Vec<R>)
+        {
+                let mut out = Vec::new();
+                let rows = self.write_lp(&mut out, cost).expect("Writing to a vector cannot fail");
+                (String::from_utf8(out).expect("Names are valid UTF-8"), rows)
+        }
+
+        // Like write_mps, but to a string.
+        pub
+        fn to_mps <F: Fn(&R) -> f64> (&self, cost: F) -> (String, Vec<R>)
+        {
+                let mut out = Vec::new();
+                let rows = self.write_mps(&mut out, cost).expect("Writing to a vector cannot fail");
+                (String::from_utf8(out).expect("Names are valid UTF-8"), rows)
+        }
+}
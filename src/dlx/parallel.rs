@@ -0,0 +1,128 @@
+// Searches the subtrees below the choices at some depth on several threads.
+// Each thread works on its own fork of the array, and takes the next prefix
+// whenever it is done with one, so uneven subtrees still keep every thread busy.
+// Column choosers and row orders are Send and Sync so that the forks can take them to other threads.
+// Each fork gets its own copy from their fork method, in the state they are in at the split.
+
+use super::dancing_link_array_optional::{DancingLinkArray, NodeIdx, Prefix, Search};
+use super::limits::Limits;
+use super::stats::SearchStats;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+// Runs work on every prefix at split_depth, and returns the results in the order
+// the prefixes are found by a serial search. Prefixes that were not started
+// because stop was set are left out.
+// work gets a fork of dla with the prefix applied, and has to leave it that way.
+fn run <T, F> (dla: &mut DancingLinkArray, split_depth: usize, stop: &AtomicBool, work: F) -> Vec<T>
+where
+        T: Send,
+        F: Fn(&mut DancingLinkArray, &Prefix, &mut SearchStats) -> T + Sync,
+{
+        let mut search = Search::new();
+        let mut prefixes = Vec::new();
+        while search.next_prefix(dla, split_depth) {
+                prefixes.push(search.prefix());
+        }
+        let mut stats = search.take_stats();
+
+        let num_threads = thread::available_parallelism().map_or(1, |n| n.get()).min(prefixes.len());
+        let forks: Vec<DancingLinkArray> = (0..num_threads).map(|_| dla.fork()).collect();
+        let next = AtomicUsize::new(0);
+
+        let per_thread: Vec<(Vec<(usize, T)>, SearchStats)> = thread::scope(|scope| {
+                let handles: Vec<_> = forks.into_iter().map(|mut fork| {
+                        let (prefixes, next, work) = (&prefixes, &next, &work);
+                        scope.spawn(move || {
+                                let mut results = Vec::new();
+                                let mut stats = SearchStats::default();
+                                while !stop.load(Ordering::Relaxed) {
+                                        let i = next.fetch_add(1, Ordering::Relaxed);
+                                        let Some(prefix) = prefixes.get(i) else {
+                                                break;
+                                        };
+                                        prefix.apply(&mut fork);
+                                        let mut prefix_stats = SearchStats::default();
+                                        results.push((i, work(&mut fork, prefix, &mut prefix_stats)));
+                                        stats.add(&prefix_stats, prefix.depth());
+                                        prefix.undo(&mut fork);
+                                }
+                                (results, stats)
+                        })
+                }).collect();
+                handles.into_iter().map(|handle| handle.join().expect("Search thread panicked")).collect()
+        });
+
+        let mut results = Vec::new();
+        for (thread_results, thread_stats) in per_thread {
+                results.extend(thread_results);
+                stats.add(&thread_stats, 0);
+        }
+        dla.set_last_stats(stats);
+        results.sort_unstable_by_key(|&(i, _)| i);
+        results.into_iter().map(|(_, t)| t).collect()
+}
+
+// The prefix followed by the rows the search below it chose.
+fn solution (dla: &DancingLinkArray, prefix: &Prefix, search: &Search) -> Vec<NodeIdx>
+{
+        prefix.chosen_rows(dla).chain(search.chosen_rows(dla)).collect()
+}
+
+// Returns all solutions, in the order solve_many finds them
+// if the heuristics can be forked.
+pub
+fn solve_many (dla: &mut DancingLinkArray, split_depth: usize) -> Vec <Vec<NodeIdx>>
+{
+        let stop = AtomicBool::new(false);
+        let per_prefix = run(dla, split_depth, &stop, |fork, prefix, stats| {
+                let mut search = Search::new();
+                let mut sols = Vec::new();
+                while search.next_solution(fork) {
+                        sols.push(solution(fork, prefix, &search));
+                }
+                *stats = search.take_stats();
+                sols
+        });
+        per_prefix.into_iter().flatten().collect()
+}
+
+pub
+fn count_solutions (dla: &mut DancingLinkArray, split_depth: usize) -> u128
+{
+        let stop = AtomicBool::new(false);
+        let per_prefix = run(dla, split_depth, &stop, |fork, _prefix, stats| {
+                let mut search = Search::new();
+                let mut cnt: u128 = 0;
+                while search.next_solution(fork) {
+                        cnt += 1;
+                }
+                *stats = search.take_stats();
+                cnt
+        });
+        per_prefix.into_iter().sum()
+}
+
+// Returns the solution that is found first by any thread.
+// Which one that is depends on timing.
+pub
+fn solve_one (dla: &mut DancingLinkArray, split_depth: usize) -> Option <Vec<NodeIdx>>
+{
+        // The other threads give up on their subtree as soon as one thread succeeds.
+        let stop = Arc::new(AtomicBool::new(false));
+        let limits = Limits::new().with_cancel(stop.clone());
+        let per_prefix = run(dla, split_depth, &stop, |fork, prefix, stats| {
+                let mut search = Search::new();
+                let sol = if search.next_solution_limited(fork, &limits, None) == Some (true) {
+                        stop.store(true, Ordering::Relaxed);
+                        Some (solution(fork, prefix, &search))
+                } else {
+                        None
+                };
+                search.reset(fork);
+                *stats = search.take_stats();
+                sol
+        });
+        per_prefix.into_iter().flatten().next()
+}
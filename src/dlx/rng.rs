@@ -0,0 +1,36 @@
+// A small deterministic pseudo random number generator (SplitMix64),
+// so that randomised searches are reproducible without pulling in a dependency.
+// Not meant for anything that needs real randomness.
+
+#[derive(Clone, Debug)]
+pub
+struct Rng {
+        state: u64,
+}
+
+impl Rng {
+        pub
+        fn new (seed: u64) -> Rng
+        {
+                Rng {state: seed}
+        }
+
+        pub
+        fn next_u64 (&mut self) -> u64
+        {
+                self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = self.state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^ (z >> 31)
+        }
+
+        // Uniform in 0..n, for n > 0.
+        // Uses the multiply-shift trick, whose bias is negligible for the small n we need.
+        pub
+        fn below (&mut self, n: usize) -> usize
+        {
+                debug_assert!(n > 0);
+                ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+        }
+}
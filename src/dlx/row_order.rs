@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+use std::ops::Deref;
+
+use super::rng::Rng;
+
+// A row that can cover the column the search branches on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub
+struct RowInfo {
+        // The index of the row, in the order the rows were given at construction.
+        pub index:              usize,
+
+        // Where the row is in the array, so the search can find it back after reordering.
+        pub(crate) node:        u32,
+}
+
+// The rows a row order is given. They can be read like a slice,
+// but only be reordered, so the search always gets every row back exactly once.
+#[derive(Debug)]
+pub
+struct Rows <'a> {
+        rows: &'a mut [RowInfo],
+}
+
+impl <'a> Rows <'a> {
+        pub(crate)
+        fn new (rows: &'a mut [RowInfo]) -> Rows <'a>
+        {
+                Rows {rows}
+        }
+
+        pub
+        fn swap (&mut self, a: usize, b: usize)
+        {
+                self.rows.swap(a, b);
+        }
+
+        pub
+        fn reverse (&mut self)
+        {
+                self.rows.reverse();
+        }
+
+        // Stable, like the sorts of slices.
+        pub
+        fn sort_by <F: FnMut(&RowInfo, &RowInfo) -> Ordering> (&mut self, compare: F)
+        {
+                self.rows.sort_by(compare);
+        }
+
+        pub
+        fn sort_by_key <K: Ord, F: FnMut(&RowInfo) -> K> (&mut self, key: F)
+        {
+                self.rows.sort_by_key(key);
+        }
+}
+
+impl Deref for Rows <'_> {
+        type Target = [RowInfo];
+
+        fn deref (&self) -> &[RowInfo]
+        {
+                self.rows
+        }
+}
+
+// Decides in which order the search tries the rows of the column it branches on.
+// This does not change which solutions exist, only the order in which they are found.
+pub
+trait RowOrder: Send + Sync {
+        // Is given the rows of strict column col that are still available,
+        // in their default order, and sorts them into the order they should be tried.
+        fn order (&mut self, col: usize, rows: &mut Rows);
+
+        // A copy for a fork of the array, see parallel.rs.
+        // Row orders that cannot be copied return None, and the fork uses the default order.
+        fn fork (&self) -> Option<Box<dyn RowOrder>>
+        {
+                None
+        }
+}
+
+// Any closure over the column and its rows works as a row order.
+impl <F: FnMut(usize, &mut Rows) + Send + Sync> RowOrder for F {
+        fn order (&mut self, col: usize, rows: &mut Rows)
+        {
+                self(col, rows)
+        }
+}
+
+// Tries rows with a lower key first, keeping the default order on ties.
+// keys[i] belongs to row i. Rows without a key, e.g. added later, are tried last.
+#[derive(Clone, Debug)]
+pub
+struct ByKey <K: Ord> {
+        keys: Box<[K]>,
+}
+
+impl <K: Ord> ByKey <K> {
+        pub
+        fn new (keys: Box<[K]>) -> ByKey <K>
+        {
+                ByKey {keys}
+        }
+}
+
+impl <K: Ord + Clone + Send + Sync + 'static> RowOrder for ByKey <K> {
+        fn order (&mut self, _col: usize, rows: &mut Rows)
+        {
+                rows.sort_by(|a, b| match (self.keys.get(a.index), self.keys.get(b.index)) {
+                        (Some (ka), Some (kb))  => ka.cmp(kb),
+                        (ka, kb)                => ka.is_none().cmp(&kb.is_none()),
+                });
+        }
+
+        fn fork (&self) -> Option<Box<dyn RowOrder>>
+        {
+                Some (Box::new(self.clone()))
+        }
+}
+
+// Tries the rows in a random order, with a seeded generator so the order is reproducible.
+#[derive(Clone, Debug)]
+pub
+struct Shuffle {
+        rng: Rng,
+}
+
+impl Shuffle {
+        pub
+        fn new (seed: u64) -> Shuffle
+        {
+                Shuffle {rng: Rng::new(seed)}
+        }
+}
+
+impl RowOrder for Shuffle {
+        fn order (&mut self, _col: usize, rows: &mut Rows)
+        {
+                // Fisher-Yates.
+                for i in (1..rows.len()).rev() {
+                        let j = self.rng.below(i + 1);
+                        rows.swap(i, j);
+                }
+        }
+
+        fn fork (&self) -> Option<Box<dyn RowOrder>>
+        {
+                Some (Box::new(self.clone()))
+        }
+}
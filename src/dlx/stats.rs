@@ -0,0 +1,84 @@
+// What a search did, to compare encodings and heuristics.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub
+struct SearchStats {
+        // The number of row choices, counting every branch that was tried.
+        pub nodes:              u64,
+
+        // The number of times a node was unlinked or relinked, by covering or purifying columns,
+        // ruling out rows, and taking all of that back.
+        pub updates:            u64,
+
+        // The number of solutions found.
+        pub solutions:          u64,
+
+        // The largest number of choices made at once.
+        pub max_depth:          usize,
+
+        // nodes_per_depth[d] is the number of row choices made with d choices already made.
+        // This sums to nodes, and shows where the search tree is wide.
+        pub nodes_per_depth:    Vec<u64>,
+}
+
+impl SearchStats {
+        pub(crate)
+        fn record_node (&mut self, depth: usize)
+        {
+                self.nodes += 1;
+                if self.nodes_per_depth.len() <= depth {
+                        self.nodes_per_depth.resize(depth + 1, 0);
+                        self.max_depth = depth + 1;
+                }
+                self.nodes_per_depth[depth] += 1;
+        }
+
+        // Adds the statistics of a search that started with depth choices already made.
+        pub(crate)
+        fn add (&mut self, other: &SearchStats, depth: usize)
+        {
+                self.nodes += other.nodes;
+                self.updates += other.updates;
+                self.solutions += other.solutions;
+                if self.nodes_per_depth.len() < depth + other.nodes_per_depth.len() {
+                        self.nodes_per_depth.resize(depth + other.nodes_per_depth.len(), 0);
+                }
+                for (d, &n) in other.nodes_per_depth.iter().enumerate() {
+                        self.nodes_per_depth[depth + d] += n;
+                }
+                self.max_depth = self.nodes_per_depth.len();
+        }
+}
+
+// Calls callback every so many nodes during a search,
+// with the number of choices made and the row indices chosen so far.
+// Given to each search, so the callback can look at whatever the rows are by then.
+pub(crate)
+struct Progress <'a> {
+        pub(crate) every:       u64,
+        pub(crate) callback:    &'a mut dyn FnMut(usize, &[u32]),
+}
+
+// Holds a value that is only ever used through a &mut, like the progress callback.
+// Nothing of the value can be reached through a &Exclusive, so it can be shared
+// between threads even if the value is not Sync, and the callback does not have to be.
+pub(crate)
+struct Exclusive <T> {
+        value: T,
+}
+
+impl <T> Exclusive <T> {
+        pub(crate)
+        fn new (value: T) -> Exclusive <T>
+        {
+                Exclusive {value}
+        }
+
+        pub(crate)
+        fn get_mut (&mut self) -> &mut T
+        {
+                &mut self.value
+        }
+}
+
+// Only get_mut gives access to the value, and it needs a &mut.
+unsafe impl <T: Send> Sync for Exclusive <T> {}
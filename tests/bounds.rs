@@ -0,0 +1,67 @@
+// Strict columns with lower and upper bounds, on small hand-counted instances.
+
+use dancing_links_x::dlx::UCSolver;
+
+fn sorted (mut sols: Vec<Vec<usize>>) -> Vec<Vec<usize>>
+{
+        for sol in &mut sols {
+                sol.sort();
+        }
+        sols.sort();
+        sols
+}
+
+#[test]
+fn choose_two_or_three ()
+{
+        // Four rows that only cover 'a', which needs two or three of them.
+        let rows = [0, 1, 2, 3];
+        let mut solver = UCSolver::from_pred_bounded(&rows, &['a'], &[(2, 3)], &[], |_, _: &char| true);
+        let sols = sorted(solver.solve_many());
+        assert_eq!(sols.len(), 6 + 4);
+        assert!(sols.iter().all(|sol| (2..=3).contains(&sol.len())));
+        assert_eq!(solver.count_solutions(None), 10);
+}
+
+#[test]
+fn exact_and_bounded_columns ()
+{
+        // Row 0 is {a}, row 1 is {a, b}, rows 2 and 3 are {b}.
+        // Column a is needed once, and b at most twice.
+        let rows = [0, 1, 2, 3];
+        let has = |&r: &usize, &c: &char| if c == 'a' {r < 2} else {r > 0};
+        let mut solver = UCSolver::from_pred_bounded(&rows, &['a', 'b'], &[(1, 1), (0, 2)], &[], has);
+        assert_eq!(sorted(solver.solve_many()), [
+                vec![0], vec![0, 2], vec![0, 2, 3], vec![0, 3],
+                vec![1], vec![1, 2], vec![1, 3],
+        ]);
+}
+
+#[test]
+fn bounds_with_optional_columns ()
+{
+        // As above, but rows 2 and 3 also share the optional column x.
+        let rows = [0, 1, 2, 3];
+        let has = |&r: &usize, &c: &char| match c {
+                'a' => r < 2,
+                'b' => r > 0,
+                _ => r > 1,
+        };
+        let mut solver = UCSolver::from_pred_bounded(&rows, &['a', 'b'], &[(1, 1), (0, 2)], &['x'], has);
+        assert_eq!(sorted(solver.solve_many()), [
+                vec![0], vec![0, 2], vec![0, 3],
+                vec![1], vec![1, 2], vec![1, 3],
+        ]);
+        // Forcing row 1 fills b up to one, leaving room for one more.
+        assert_eq!(solver.count_solutions_with(&[1], None), 3);
+}
+
+#[test]
+fn lower_bound_not_reachable ()
+{
+        // Only two rows cover 'a', which needs three.
+        let rows = [0, 1];
+        let mut solver = UCSolver::from_pred_bounded(&rows, &['a'], &[(3, 4)], &[], |_, _: &char| true);
+        assert_eq!(solver.solve_one(), None);
+        assert_eq!(solver.count_solutions(None), 0);
+}
@@ -0,0 +1,88 @@
+// The DIMACS CNF of small instances, clause by clause.
+
+use dancing_links_x::dlx::{CnfEncoding, Color, UCSolver};
+
+fn cnf (solver: &UCSolver<usize>, encoding: CnfEncoding) -> (String, Vec<usize>)
+{
+        let mut out = Vec::new();
+        let rows = solver.to_cnf(&mut out, encoding).unwrap();
+        (String::from_utf8(out).unwrap(), rows)
+}
+
+#[test]
+fn pairwise ()
+{
+        // Column a has rows 0 and 2, b has 0, 3 and 4, and c has 1, 4 and 5.
+        const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        let solver = UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c));
+        assert_eq!(cnf(&solver, CnfEncoding::Pairwise), (
+                "c Variables 1 to 6 are the rows, the rest belong to the encoding.\n\
+                 p cnf 6 10\n\
+                 1 3 0\n-1 -3 0\n\
+                 1 4 5 0\n-1 -4 0\n-1 -5 0\n-4 -5 0\n\
+                 2 5 6 0\n-2 -5 0\n-2 -6 0\n-5 -6 0\n".to_string(),
+                rows,
+        ));
+}
+
+#[test]
+fn sequential ()
+{
+        let solver = UCSolver::from_pred(&[10, 11, 12], &['a'], |_, _| true);
+        // Variable 4 tells that row 10 is chosen, and 5 that row 10 or 11 is.
+        assert_eq!(cnf(&solver, CnfEncoding::Sequential), (
+                "c Variables 1 to 3 are the rows, the rest belong to the encoding.\n\
+                 p cnf 5 6\n\
+                 1 2 3 0\n\
+                 -1 4 0\n-2 5 0\n-4 5 0\n-2 -4 0\n-3 -5 0\n".to_string(),
+                vec![10, 11, 12],
+        ));
+}
+
+#[test]
+fn commander ()
+{
+        let rows: Vec<usize> = (0..5).collect();
+        let solver = UCSolver::from_pred(&rows, &['a'], |_, _| true);
+        // Rows 0 to 2 have commander 6, and rows 3 and 4 have commander 7.
+        assert_eq!(cnf(&solver, CnfEncoding::Commander).0,
+                "c Variables 1 to 5 are the rows, the rest belong to the encoding.\n\
+                 p cnf 7 13\n\
+                 1 2 3 4 5 0\n\
+                 -1 -2 0\n-1 -3 0\n-2 -3 0\n-6 1 2 3 0\n-1 6 0\n-2 6 0\n-3 6 0\n\
+                 -4 -5 0\n-7 4 5 0\n-4 7 0\n-5 7 0\n\
+                 -6 -7 0\n");
+}
+
+#[test]
+fn colored ()
+{
+        // Rows 0 and 1 give x the same color, so they stand behind variable 4, and row 2 claims x.
+        let rows = [0, 1, 2];
+        let has = |&r: &usize, &c: &char| match c {
+                'p' => r == 0,
+                'q' => r > 0,
+                _ => true,
+        };
+        let color = |&r: &usize, _: &char| if r < 2 {Color::new(1)} else {None};
+        let solver = UCSolver::from_pred_colored(&rows, &['p', 'q'], &['x'], has, color);
+        assert_eq!(cnf(&solver, CnfEncoding::Pairwise).0,
+                "c Variables 1 to 3 are the rows, the rest belong to the encoding.\n\
+                 p cnf 4 6\n\
+                 1 0\n\
+                 2 3 0\n-2 -3 0\n\
+                 -1 4 0\n-2 4 0\n-4 -3 0\n");
+}
+
+#[test]
+fn rows_without_strict_columns ()
+{
+        // Row 1 only has the optional column, so it is never chosen.
+        let solver = UCSolver::from_pred_opt(&[0, 1], &['a'], &['x'], |&r, &c| (r == 0) == (c == 'a'));
+        assert_eq!(cnf(&solver, CnfEncoding::Pairwise).0,
+                "c Variables 1 to 2 are the rows, the rest belong to the encoding.\n\
+                 p cnf 2 2\n\
+                 -2 0\n\
+                 1 0\n");
+}
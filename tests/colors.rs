@@ -0,0 +1,63 @@
+// Colored optional columns, on small hand-checked instances.
+
+use dancing_links_x::dlx::{Color, UCSolver};
+
+// Rows are written as in Knuth's examples, e.g. "p x:A y",
+// where x is given color A and y is claimed by the row.
+fn solver (rows: &[&'static str], strict: &[&'static str], opt: &[&'static str]) -> UCSolver<&'static str>
+{
+        let has = |r: &&str, c: &&str| r.split(' ').any(|item| item.split(':').next() == Some (c));
+        let color = |r: &&str, c: &&str| {
+                r.split(' ')
+                        .find_map(|item| item.strip_prefix(c)?.strip_prefix(':'))
+                        .map(|name| Color::new(u32::from(name.as_bytes()[0])).unwrap())
+        };
+        UCSolver::from_pred_colored(rows, strict, opt, has, color)
+}
+
+fn sorted (mut sols: Vec<Vec<&'static str>>) -> Vec<Vec<&'static str>>
+{
+        for sol in &mut sols {
+                sol.sort();
+        }
+        sols.sort();
+        sols
+}
+
+#[test]
+fn knuths_example ()
+{
+        // The example from TAOCP 7.2.2.1, with the single solution "q x:A" and "p r x:A y".
+        let rows = ["p q x y:A", "p r x:A y", "p x:B", "q x:A", "r y:B"];
+        let mut solver = solver(&rows, &["p", "q", "r"], &["x", "y"]);
+        assert_eq!(sorted(solver.solve_many()), [["p r x:A y", "q x:A"]]);
+        assert_eq!(solver.count_solutions(None), 1);
+}
+
+#[test]
+fn same_color_is_shared ()
+{
+        let rows = ["p x:A", "q x:A", "q x:B", "q x"];
+        let mut solver = solver(&rows, &["p", "q"], &["x"]);
+        // Only the row that agrees with "p x:A" on x can go with it.
+        assert_eq!(sorted(solver.solve_many()), [["p x:A", "q x:A"]]);
+}
+
+#[test]
+fn uncolored_rows_claim_the_column ()
+{
+        let rows = ["p x", "q x", "q", "p"];
+        let mut solver = solver(&rows, &["p", "q"], &["x"]);
+        assert_eq!(sorted(solver.solve_many()), [vec!["p", "q"], vec!["p", "q x"], vec!["p x", "q"]]);
+}
+
+#[test]
+fn colors_across_several_columns ()
+{
+        // Every row needs x and y to agree with the others.
+        let rows = ["p x:A y:B", "q x:A y:B", "r x:A y:C", "r y:B", "r x:B"];
+        let mut solver = solver(&rows, &["p", "q", "r"], &["x", "y"]);
+        assert_eq!(sorted(solver.solve_many()), [["p x:A y:B", "q x:A y:B", "r y:B"]]);
+        // Forcing the clashing row leaves nothing.
+        assert_eq!(solver.solve_one_with(&["r x:A y:C"]), None);
+}
@@ -0,0 +1,149 @@
+// Building large matrices has to take time linear in their number of nodes.
+// And solvers built with closures can still be shared between threads.
+
+use dancing_links_x::dlx::{ColumnKind, Rows, UCSolver};
+use std::time::{Duration, Instant};
+
+// N-queens: rows and columns are strict, the diagonals optional.
+// Gives the columns of each placement, with the strict columns first.
+fn queens (n: usize) -> (Vec<usize>, Vec<usize>, Vec<usize>, Vec<[usize; 4]>)
+{
+        let strict_cols: Vec<usize> = (0..2 * n).collect();
+        let opt_cols: Vec<usize> = (0..2 * (2 * n - 1)).collect();
+        let placements: Vec<usize> = (0..n * n).collect();
+        let cols = placements.iter().map(|&p| {
+                let (r, c) = (p / n, p % n);
+                [r, n + c, 2 * n + r + c, 2 * n + (2 * n - 1) + r + n - 1 - c]
+        }).collect();
+        (placements, strict_cols, opt_cols, cols)
+}
+
+// The fastest of a few runs, to be less sensitive to noise.
+// The columns are worked out beforehand, so this times little more than linking the array.
+fn build_time (n: usize) -> Duration
+{
+        let (placements, strict_cols, opt_cols, cols) = queens(n);
+        (0..3).map(|_| {
+                let start = Instant::now();
+                let solver = UCSolver::from_row_fn_opt(&placements, &strict_cols, &opt_cols, |&p| cols[p]);
+                let dur = start.elapsed();
+                drop(solver);
+                dur
+        }).min().unwrap()
+}
+
+// The ratio leaves plenty of room for noise and unoptimised builds,
+// but linking with a scan per row and column would still exceed it.
+#[test]
+fn construction_is_linear ()
+{
+        // The large instance has 16 times the nodes and about 4 times the rows and columns,
+        // so such a scan would take about 64 times longer on top.
+        let (t_small, t_large) = (build_time(25), build_time(100));
+        println!("Built 25-queens in {t_small:?} and 100-queens in {t_large:?}");
+        assert!(t_large < t_small * 64 + Duration::from_millis(5),
+                "Construction does not scale linearly: {t_small:?} against {t_large:?}");
+}
+
+#[test]
+fn queens_are_counted ()
+{
+        // 8-queens has its well-known number of solutions.
+        let (placements, strict_cols, opt_cols, cols) = queens(8);
+        let mut solver = UCSolver::from_row_fn_opt(&placements, &strict_cols, &opt_cols, |&p| cols[p]);
+        assert_eq!(solver.count_solutions(None), 92);
+}
+
+fn sorted (mut sols: Vec<Vec<&'static str>>) -> Vec<Vec<&'static str>>
+{
+        for sol in &mut sols {
+                sol.sort();
+        }
+        sols.sort();
+        sols
+}
+
+#[test]
+fn from_the_columns_of_each_row ()
+{
+        // Column a has rows "ab" and "a", b has "ab", "b" and "bc", and c has "c", "bc" and "c2".
+        let rows = ["ab", "c", "a", "b", "bc", "c2"];
+        let cols = ['a', 'b', 'c'];
+        let expected = [vec!["a", "b", "c"], vec!["a", "b", "c2"], vec!["a", "bc"], vec!["ab", "c"], vec!["ab", "c2"]];
+
+        // The columns may come in any order.
+        let index = |c: char| cols.iter().position(|&col| col == c).unwrap();
+        let mut solver = UCSolver::from_row_fn(&rows, &cols, |r| r.chars().filter(|c| c.is_alphabetic()).map(index).rev());
+        assert_eq!(sorted(solver.solve_many()), expected);
+
+        let mut solver = UCSolver::from_row_vals(&rows, &cols, &[], |r| r.chars().filter(|c| c.is_alphabetic()));
+        assert_eq!(sorted(solver.solve_many()), expected);
+}
+
+#[test]
+fn from_the_columns_of_each_row_with_optional_columns ()
+{
+        // "x" is optional, so "a x" and "b x" cannot both be chosen.
+        let rows = ["a x", "b x", "a", "b"];
+        let mut solver = UCSolver::from_row_fn_opt(&rows, &["a", "b"], &["x"], |r| {
+                r.split(' ').map(|c| match c {"a" => 0, "b" => 1, _ => 2}).collect::<Vec<_>>()
+        });
+        assert_eq!(sorted(solver.solve_many()), [vec!["a", "b"], vec!["a", "b x"], vec!["a x", "b"]]);
+
+        let mut solver = UCSolver::from_row_vals(&rows, &["a", "b"], &["x"], |r| r.split(' '));
+        assert_eq!(sorted(solver.solve_many()), [vec!["a", "b"], vec!["a", "b x"], vec!["a x", "b"]]);
+}
+
+#[test]
+#[should_panic(expected = "Row 1 covers column 0 more than once")]
+fn duplicate_column_in_a_row ()
+{
+        UCSolver::from_row_fn(&[0, 1], &['a', 'b'], |&r| if r == 0 {vec![1]} else {vec![0, 1, 0]});
+}
+
+#[test]
+#[should_panic(expected = "Row 0 covers column 2, which does not exist")]
+fn column_out_of_range ()
+{
+        UCSolver::from_row_fn(&[0], &['a', 'b'], |_| [2]);
+}
+
+#[test]
+fn from_pairs_with_hashing ()
+{
+        // Rows "xy" and "yz" share the optional column, so they cannot both be chosen.
+        let pairs = [
+                ("xy", "a"), ("xy", "opt"),
+                ("z", "b"),
+                ("yz", "b"), ("yz", "opt"),
+                ("x", "a"),
+        ];
+        let kind = |c: &&str| if *c == "opt" {ColumnKind::Optional} else {ColumnKind::Strict};
+        let mut solver = UCSolver::from_it_hashed(pairs, kind).unwrap();
+        assert_eq!(sorted(solver.solve_many()), [vec!["x", "yz"], vec!["x", "z"], vec!["xy", "z"]]);
+}
+
+#[test]
+fn from_pairs_with_a_duplicate ()
+{
+        let pairs = [("r", 'a'), ("s", 'b'), ("r", 'b'), ("s", 'b')];
+        let err = UCSolver::from_it_hashed(pairs, |_| ColumnKind::Strict).err().unwrap();
+        assert_eq!((err.row, err.col), ("s", 'b'));
+}
+
+#[test]
+fn solvers_are_send_and_sync ()
+{
+        // Also with everything a solver can keep a closure for.
+        fn send_and_sync <T: Send + Sync> (_: &T) {}
+        let (placements, strict_cols, opt_cols, cols) = queens(4);
+        let mut solver = UCSolver::from_row_fn_opt(&placements, &strict_cols, &opt_cols, |&p| cols[p])
+                .with_row_costs(|&p| p as u64);
+        solver.set_column_chooser(|infos: &[_]| infos.len() - 1);
+        solver.set_row_order(|_, rows: &mut Rows| rows.reverse());
+        // The progress callback does not have to be Sync, only the solver calls it.
+        let calls = std::cell::Cell::new(0);
+        solver.set_progress(std::num::NonZeroU64::MIN, move |_, _| calls.set(calls.get() + 1));
+        send_and_sync(&solver);
+        assert_eq!(solver.count_solutions(None), 2);
+}
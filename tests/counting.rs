@@ -0,0 +1,56 @@
+// Counting solutions, with and without a cap.
+
+use dancing_links_x::dlx::UCSolver;
+
+// The rows are all nonempty subsets of n columns, so the solutions are
+// the set partitions of n elements, counted by the Bell numbers.
+fn partitions (n: u32) -> UCSolver<u32>
+{
+        let rows: Vec<u32> = (1..1 << n).collect();
+        let cols: Vec<u32> = (0..n).collect();
+        UCSolver::from_pred(&rows, &cols, |&r, &c| r & (1 << c) != 0)
+}
+
+#[test]
+fn count_partitions ()
+{
+        for (n, bell) in [(1, 1), (2, 2), (3, 5), (4, 15), (5, 52)] {
+                let mut solver = partitions(n);
+                assert_eq!(solver.count_solutions(None), bell);
+                assert_eq!(solver.solve_many().len() as u128, bell);
+        }
+}
+
+#[test]
+fn count_with_cap ()
+{
+        let mut solver = partitions(4);
+        assert_eq!(solver.count_solutions(Some (0)), 0);
+        assert_eq!(solver.count_solutions(Some (1)), 1);
+        assert_eq!(solver.count_solutions(Some (2)), 2);
+        assert_eq!(solver.count_solutions(Some (15)), 15);
+        assert_eq!(solver.count_solutions(Some (100)), 15);
+        // Stopping early leaves the solver as it was.
+        assert_eq!(solver.count_solutions(None), 15);
+}
+
+#[test]
+fn count_with_forced_rows ()
+{
+        let mut solver = partitions(4);
+        // With {0, 1} forced, columns 2 and 3 are left: {2, 3} or {2} and {3}.
+        assert_eq!(solver.count_solutions_with(&[0b0011], None), 2);
+        // With {0} and {3} forced, {1, 2} or {1} and {2}.
+        assert_eq!(solver.count_solutions_with(&[0b0001, 0b1000], Some (1)), 1);
+        assert_eq!(solver.count_solutions_with(&[0b0001, 0b1000], None), 2);
+        assert_eq!(solver.count_solutions(None), 15);
+}
+
+#[test]
+fn count_without_solutions ()
+{
+        // Row 0 is the only row with 'b' and row 1 the only one with 'c', but both have 'a'.
+        let mut solver = UCSolver::from_pred(&[0, 1], &['a', 'b', 'c'], |&r, &c| c == 'a' || (r == 0) == (c == 'b'));
+        assert_eq!(solver.count_solutions(None), 0);
+        assert_eq!(solver.count_solutions(Some (1)), 0);
+}
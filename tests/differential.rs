@@ -0,0 +1,1092 @@
+// Checks the solver against a brute-force enumeration of all subsets of rows,
+// on every small matrix and on many random slightly larger ones.
+
+use dancing_links_x::dlx::{Color, UCSolver};
+use std::num::NonZeroU64;
+
+// A 0/1 matrix, with the first num_strict columns strict and the rest optional.
+// Cells in optional columns may also be colored:
+// 0 is an empty cell, 1 an uncolored one, and anything higher a color.
+// Strict columns are covered a number of times within their bounds, usually exactly once.
+struct Matrix {
+        num_rows:       usize,
+        num_strict:     usize,
+        num_opt:        usize,
+        cells:          Vec<u8>,
+        bounds:         Vec<(u32, u32)>,
+}
+
+impl Matrix {
+        fn num_cols (&self) -> usize
+        {
+                self.num_strict + self.num_opt
+        }
+
+        fn get (&self, r: usize, c: usize) -> bool
+        {
+                self.cell(r, c) != 0
+        }
+
+        fn cell (&self, r: usize, c: usize) -> u8
+        {
+                self.cells[r * self.num_cols() + c]
+        }
+
+        fn color (&self, r: usize, c: usize) -> Option<Color>
+        {
+                Color::new(u32::from(self.cell(r, c)) - 1)
+        }
+
+        fn is_colored (&self) -> bool
+        {
+                self.cells.iter().any(|&cell| cell > 1)
+        }
+
+        // The constructors do not accept empty rows, though empty columns are fine.
+        fn has_empty_row (&self) -> bool
+        {
+                (0..self.num_rows).any(|r| (0..self.num_cols()).all(|c| !self.get(r, c)))
+        }
+
+        // from_it and the like only know the columns that are in some row.
+        fn has_empty_column (&self) -> bool
+        {
+                (0..self.num_cols()).any(|c| (0..self.num_rows).all(|r| !self.get(r, c)))
+        }
+
+        fn solver (&self) -> UCSolver<usize>
+        {
+                let rows: Vec<usize> = (0..self.num_rows).collect();
+                let strict_cols: Vec<usize> = (0..self.num_strict).collect();
+                let opt_cols: Vec<usize> = (self.num_strict..self.num_cols()).collect();
+                let bounded = self.bounds.iter().any(|&b| b != (1, 1));
+                if bounded && self.is_colored() {
+                        UCSolver::from_pred_colored(&rows, &strict_cols, &opt_cols, |&r, &c| self.get(r, c), |&r, &c| self.color(r, c))
+                                .with_bounds(&self.bounds).unwrap()
+                } else if bounded {
+                        UCSolver::from_pred_bounded(&rows, &strict_cols, &self.bounds, &opt_cols, |&r, &c| self.get(r, c))
+                } else if self.is_colored() {
+                        UCSolver::from_pred_colored(&rows, &strict_cols, &opt_cols, |&r, &c| self.get(r, c), |&r, &c| self.color(r, c))
+                } else if self.num_opt == 0 {
+                        UCSolver::from_pred(&rows, &strict_cols, |&r, &c| self.get(r, c))
+                } else {
+                        UCSolver::from_pred_opt(&rows, &strict_cols, &opt_cols, |&r, &c| self.get(r, c))
+                }
+        }
+
+        // Every subset of rows that covers the strict columns within their bounds,
+        // and the optional columns at most once, unless all rows give it the same color.
+        // As in Knuth's formulation, rows without any strict column are never chosen.
+        fn brute_force (&self) -> Vec<Vec<usize>>
+        {
+                let choosable: Vec<bool> = (0..self.num_rows).map(|r| (0..self.num_strict).any(|c| self.get(r, c))).collect();
+                let mut sols = Vec::new();
+                for subset in 0u32..(1 << self.num_rows) {
+                        let chosen: Vec<usize> = (0..self.num_rows).filter(|r| subset & (1 << r) != 0).collect();
+                        if chosen.iter().any(|&r| !choosable[r]) {
+                                continue;
+                        }
+                        let valid = (0..self.num_cols()).all(|c| {
+                                let users: Vec<usize> = chosen.iter().copied().filter(|&r| self.get(r, c)).collect();
+                                if c < self.num_strict {
+                                        let (lo, hi) = self.bounds[c];
+                                        (lo as usize..=hi as usize).contains(&users.len())
+                                } else {
+                                        let shared_color = users.iter().all(|&r| self.color(r, c).is_some() && self.cell(r, c) == self.cell(users[0], c));
+                                        users.len() <= 1 || shared_color
+                                }
+                        });
+                        if valid {
+                                sols.push(chosen);
+                        }
+                }
+                sols
+        }
+}
+
+// Sorts each solution and the list of solutions,
+// keeping duplicates so that they show up as a mismatch.
+fn normalise (mut sols: Vec<Vec<usize>>) -> Vec<Vec<usize>>
+{
+        sols.iter_mut().for_each(|sol| sol.sort_unstable());
+        sols.sort_unstable();
+        sols
+}
+
+fn check (m: &Matrix)
+{
+        let expected = normalise(m.brute_force());
+        let mut solver = m.solver();
+
+        let many = normalise(solver.solve_many());
+        assert_eq!(many, expected, "solve_many disagrees on a {}x{}+{} matrix", m.num_rows, m.num_strict, m.num_opt);
+
+        let lazy = normalise(solver.solutions().collect());
+        assert_eq!(lazy, expected, "solutions disagrees on a {}x{}+{} matrix", m.num_rows, m.num_strict, m.num_opt);
+
+        let num_sols = expected.len() as u128;
+        assert_eq!(solver.count_solutions(None), num_sols);
+        assert_eq!(solver.count_solutions(Some(2)), num_sols.min(2));
+
+        match solver.solve_one() {
+                Some(sol)       => assert!(expected.contains(&normalise(vec![sol]).remove(0))),
+                None            => assert!(expected.is_empty()),
+        }
+
+        // Solving must leave the solver as we found it.
+        assert_eq!(normalise(solver.solve_many()), expected);
+}
+
+// Small xorshift generator, so the tests are reproducible without dependencies.
+struct XorShift (u64);
+
+impl XorShift {
+        fn next (&mut self) -> u64
+        {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+        }
+
+        fn below (&mut self, n: u64) -> usize
+        {
+                (self.next() % n) as usize
+        }
+        fn between (&mut self, (lo, hi): (usize, usize)) -> usize
+        {
+                lo + self.below((hi - lo + 1) as u64)
+        }
+}
+
+// What random_matrix generates. Ranges are inclusive, and picked from for each matrix.
+#[derive(Clone, Copy)]
+struct Opts {
+        rows:               (usize, usize),
+        strict:             (usize, usize),
+        opt:                (usize, usize),
+        // One in how many cells is set. Sparse matrices have more interesting solutions.
+        density:            (usize, usize),
+        // The number of colors a cell of an optional column may get, besides none.
+        colors:             u8,
+        // Whether only about half of the matrices get colors.
+        some_colored:       bool,
+        // Each strict column gets one of these bounds, unless the matrix is colored.
+        bounds:             &'static [(u32, u32)],
+        // Whether colored matrices get bounds too.
+        colors_and_bounds:  bool,
+        // Whether columns may be empty, which from_it and the like can not express.
+        empty_columns:      bool,
+}
+
+// Exact covers of up to 12 rows, what most tests start from.
+const OPTS: Opts = Opts {
+        rows:               (1, 12),
+        strict:             (1, 5),
+        opt:                (0, 2),
+        density:            (3, 3),
+        colors:             0,
+        some_colored:       false,
+        bounds:             &[(1, 1)],
+        colors_and_bounds:  false,
+        empty_columns:      true,
+};
+
+// Strict columns that may also be covered zero times or twice.
+const LOOSE: &[(u32, u32)] = &[(1, 1), (0, 2)];
+
+// All kinds of bounds the search treats differently.
+const BOUNDS: &[(u32, u32)] = &[(0, 1), (0, 2), (1, 1), (1, 2), (1, 3), (2, 2), (2, 3), (2, 4)];
+
+fn random_matrix (rng: &mut XorShift, opts: Opts) -> Matrix
+{
+        let num_rows = rng.between(opts.rows);
+        let num_strict = rng.between(opts.strict);
+        let num_opt = rng.between(opts.opt);
+        let density = rng.between(opts.density) as u64;
+        let colored = opts.colors > 0 && (!opts.some_colored || rng.below(2) == 0);
+        let num_cols = num_strict + num_opt;
+        let mut cells: Vec<u8> = Vec::with_capacity(num_rows * num_cols);
+        for _ in 0..num_rows {
+                for c in 0..num_cols {
+                        let cell = if rng.below(density) != 0 {
+                                0
+                        } else if colored && c >= num_strict {
+                                1 + rng.below(u64::from(opts.colors) + 1) as u8
+                        } else {
+                                1
+                        };
+                        cells.push(cell);
+                }
+        }
+        let bounds = (0..num_strict).map(|_| {
+                if colored && !opts.colors_and_bounds {(1, 1)} else {opts.bounds[rng.below(opts.bounds.len() as u64)]}
+        }).collect();
+        Matrix {num_rows, num_strict, num_opt, cells, bounds}
+}
+
+// Calls f on n random matrices that the constructors accept,
+// together with the generator, for any further random choices.
+fn for_random_matrices <F: FnMut(&Matrix, &mut XorShift)> (seed: u64, n: usize, opts: Opts, mut f: F)
+{
+        let mut rng = XorShift(seed);
+        let mut checked = 0;
+        while checked < n {
+                let m = random_matrix(&mut rng, opts);
+                if m.has_empty_row() || (!opts.empty_columns && m.has_empty_column()) {
+                        continue;
+                }
+                f(&m, &mut rng);
+                checked += 1;
+        }
+}
+
+#[test]
+fn every_tiny_matrix ()
+{
+        for num_rows in 1..=3 {
+                for num_strict in 0..=3 {
+                        for num_opt in 0..=(3 - num_strict) {
+                                let num_cells = num_rows * (num_strict + num_opt);
+                                for bits in 0u32..(1 << num_cells) {
+                                        let cells = (0..num_cells).map(|i| u8::from(bits & (1 << i) != 0)).collect();
+                                        let bounds = vec![(1, 1); num_strict];
+                                        let m = Matrix {num_rows, num_strict, num_opt, cells, bounds};
+                                        if !m.has_empty_row() {
+                                                check(&m);
+                                        }
+                                }
+                        }
+                }
+        }
+}
+
+#[test]
+fn random_small_matrices ()
+{
+        let opts = Opts {strict: (0, 6), opt: (0, 3), density: (2, 5), ..OPTS};
+        for_random_matrices(0x9e37_79b9_7f4a_7c15, 2000, opts, |m, _| check(m));
+}
+
+#[test]
+fn random_colored_matrices ()
+{
+        // Mostly colored, from only a few colors.
+        let opts = Opts {opt: (1, 3), density: (2, 4), colors: 3, ..OPTS};
+        for_random_matrices(0x2545_f491_4f6c_dd1d, 2000, opts, |m, _| check(m));
+}
+
+#[test]
+fn shared_rows_between_solutions ()
+{
+        // Row 0 covers column 0, and can be completed in two ways.
+        // Both solutions contain row 0.
+        let rows = [0, 1, 2, 3, 4];
+        let cols = [0, 1, 2];
+        let cells: [&[usize]; 5] = [&[0], &[1, 2], &[1], &[2], &[0, 1, 2]];
+        let mut solver = UCSolver::from_pred(&rows, &cols, |&r, c| cells[r].contains(c));
+        let sols = normalise(solver.solve_many());
+        assert_eq!(sols, vec![vec![0, 1], vec![0, 2, 3], vec![4]]);
+}
+
+#[test]
+fn random_bounded_matrices ()
+{
+        let opts = Opts {density: (2, 4), bounds: BOUNDS, ..OPTS};
+        for_random_matrices(0xd1b5_4a32_d192_ed03, 2000, opts, |m, _| check(m));
+}
+
+#[test]
+fn random_colored_bounded_matrices ()
+{
+        let opts = Opts {opt: (1, 3), density: (2, 4), colors: 3, bounds: BOUNDS, colors_and_bounds: true, ..OPTS};
+        for_random_matrices(0x8f1b_bcdc_bb0b_6a3d, 2000, opts, |m, _| check(m));
+}
+
+#[test]
+fn heuristics_on_colored_bounded_columns ()
+{
+        use dancing_links_x::dlx::{ColumnInfo, Leftmost, MrvRandom, Priority, Rows, Shuffle};
+
+        // Bounded columns branch on more than their rows, and about half the matrices are colored too.
+        let opts = Opts {density: (2, 4), colors: 2, some_colored: true, bounds: BOUNDS, colors_and_bounds: true, ..OPTS};
+        for_random_matrices(0x1f9d_b4c3_5e7a_2b61, 500, opts, |m, rng| {
+                let expected = normalise(m.brute_force());
+
+                let priorities: Vec<i64> = (0..m.num_strict).map(|_| rng.below(3) as i64).collect();
+                let keys: Vec<u64> = (0..m.num_rows).map(|_| rng.next() % 4).collect();
+                let mut solvers = [m.solver(), m.solver(), m.solver(), m.solver()];
+                solvers[0].set_column_chooser(Leftmost);
+                solvers[0].set_row_order(|_col: usize, rows: &mut Rows| rows.reverse());
+                solvers[1].set_column_chooser(MrvRandom::new(rng.next()));
+                solvers[1].set_row_order(Shuffle::new(rng.next()));
+                solvers[2].set_column_chooser(Priority::new(&priorities));
+                solvers[2].set_row_order_by_key(|&r| keys[r]);
+                solvers[3].set_column_chooser(|cols: &[ColumnInfo]| cols.len() - 1);
+                solvers[3].set_row_order(|_col: usize, rows: &mut Rows| if rows.len() > 1 {rows.swap(0, 1)});
+                for solver in &mut solvers {
+                        assert_eq!(normalise(solver.solve_many()), expected);
+                        assert_eq!(solver.count_solutions(None), expected.len() as u128);
+                }
+        });
+}
+
+#[test]
+fn column_choosers_agree ()
+{
+        use dancing_links_x::dlx::{ColumnInfo, Leftmost, MrvRandom, Priority};
+
+        let opts = Opts {strict: (1, 6), ..OPTS};
+        for_random_matrices(0x6a09_e667_f3bc_c908, 500, opts, |m, rng| {
+                let expected = normalise(m.brute_force());
+
+                let priorities: Vec<i64> = (0..m.num_strict).map(|_| rng.below(3) as i64).collect();
+                let mut solvers = [m.solver(), m.solver(), m.solver(), m.solver()];
+                solvers[0].set_column_chooser(Leftmost);
+                solvers[1].set_column_chooser(MrvRandom::new(rng.next()));
+                solvers[2].set_column_chooser(Priority::new(&priorities));
+                solvers[3].set_column_chooser(|cols: &[ColumnInfo]| cols.len() - 1);
+                for solver in &mut solvers {
+                        assert_eq!(normalise(solver.solve_many()), expected);
+                }
+        });
+}
+
+#[test]
+fn row_orders_agree ()
+{
+        use dancing_links_x::dlx::Rows;
+
+        // Bounds make the search rule out rows it has tried, which must follow the new order.
+        let opts = Opts {bounds: LOOSE, ..OPTS};
+        for_random_matrices(0xbb67_ae85_84ca_a73b, 1000, opts, |m, rng| {
+                let expected = normalise(m.brute_force());
+
+                let mut reversed = m.solver();
+                reversed.set_row_order(|_col: usize, rows: &mut Rows| rows.reverse());
+                assert_eq!(normalise(reversed.solve_many()), expected);
+
+                // Orders can only move rows around, not copy one over another.
+                let mut swapped = m.solver();
+                swapped.set_row_order(|_col: usize, rows: &mut Rows| if rows.len() > 1 {rows.swap(0, 1)});
+                assert_eq!(normalise(swapped.solve_many()), expected);
+
+                let keys: Vec<u64> = (0..m.num_rows).map(|_| rng.next() % 4).collect();
+                let mut by_key = m.solver();
+                by_key.set_row_order_by_key(|&r| keys[r]);
+                assert_eq!(normalise(by_key.solve_many()), expected);
+                assert_eq!(by_key.count_solutions(None), expected.len() as u128);
+        });
+}
+
+#[test]
+fn row_order_decides_first_solution ()
+{
+        // Every row covers the single column on its own.
+        let rows = [0, 1, 2, 3];
+        let mut solver = UCSolver::from_pred(&rows, &[()], |_, _| true);
+        assert_eq!(solver.solve_one(), Some(vec![0]));
+        solver.set_row_order_by_key(|&r| std::cmp::Reverse(r));
+        assert_eq!(solver.solve_one(), Some(vec![3]));
+        assert_eq!(solver.solutions().collect::<Vec<_>>(), vec![vec![3], vec![2], vec![1], vec![0]]);
+}
+
+#[test]
+fn stepping_finds_the_same_solutions ()
+{
+        use dancing_links_x::dlx::Step;
+
+        let opts = Opts {colors: 2, some_colored: true, bounds: LOOSE, ..OPTS};
+        for_random_matrices(0x7137_4491_b5c0_fbcf, 500, opts, |m, rng| {
+                let mut solver = m.solver();
+                let all = solver.solve_many();
+
+                // Stepping and asking for the next solution can be mixed.
+                let mut found = Vec::new();
+                let mut sols = solver.solutions();
+                loop {
+                        if rng.below(4) == 0 {
+                                match sols.next() {
+                                        Some (sol) => found.push(sol),
+                                        None       => break,
+                                }
+                                continue;
+                        }
+                        let partial = sols.partial();
+                        assert!(partial.iter().all(|&r| r < m.num_rows));
+                        match sols.step() {
+                                Step::Continue  => {}
+                                Step::Solution  => found.push(sols.partial()),
+                                Step::Exhausted => break,
+                        }
+                }
+                assert_eq!(sols.step(), Step::Exhausted);
+                assert!(sols.partial().is_empty());
+                drop(sols);
+                assert_eq!(found, all);
+                assert_eq!(solver.solve_many(), all);
+        });
+}
+
+#[test]
+fn random_search_is_reproducible ()
+{
+        let opts = Opts {bounds: LOOSE, ..OPTS};
+        for_random_matrices(0x3c6e_f372_fe94_f82b, 1000, opts, |m, rng| {
+                let expected = normalise(m.brute_force());
+                let seed = rng.next();
+
+                let mut solver = m.solver();
+                let first = solver.solve_random(seed);
+                assert_eq!(solver.solve_random(seed), first);
+                assert_eq!(first.is_some(), !expected.is_empty());
+                let all: Vec<_> = solver.solutions_random(seed).collect();
+                assert_eq!(all.first(), first.as_ref());
+                assert_eq!(normalise(all), expected);
+
+                // The random heuristics must not stay behind.
+                assert_eq!(normalise(solver.solve_many()), expected);
+                assert_eq!(solver.solve_one(), m.solver().solve_one());
+        });
+}
+
+#[test]
+fn limits_abort_and_restore ()
+{
+        use dancing_links_x::dlx::{Limits, Outcome};
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicBool;
+        use std::time::Instant;
+
+        let opts = Opts {bounds: LOOSE, ..OPTS};
+        for_random_matrices(0xa54f_f53a_5f1d_36f1, 1000, opts, |m, rng| {
+                let expected = normalise(m.brute_force());
+                let mut solver = m.solver();
+
+                // A small budget either finishes or aborts, but never lies, and is never overrun.
+                let max_nodes = rng.below(8) as u64;
+                let budget = Limits::new().with_max_nodes(max_nodes);
+                match solver.solve_many_limited(&budget) {
+                        Outcome::Solved(sols)   => assert_eq!(normalise(sols), expected),
+                        Outcome::Unsatisfiable  => assert!(expected.is_empty()),
+                        Outcome::Aborted        => assert!(solver.stats().nodes <= max_nodes),
+                }
+                assert!(solver.stats().nodes <= max_nodes);
+                // Every row of a solution is a choice, so the budget is never overrun to find it.
+                match solver.solve_one_limited(&budget) {
+                        Outcome::Solved(sol)    => {
+                                assert!(sol.len() as u64 <= max_nodes);
+                                assert!(expected.contains(&normalise(vec![sol]).remove(0)));
+                        }
+                        Outcome::Unsatisfiable  => assert!(expected.is_empty()),
+                        Outcome::Aborted        => assert!(solver.stats().nodes <= max_nodes),
+                }
+                match solver.count_solutions_limited(None, &budget) {
+                        Outcome::Solved(n)      => assert_eq!(n, expected.len() as u128),
+                        Outcome::Unsatisfiable  => assert!(expected.is_empty()),
+                        Outcome::Aborted        => assert!(solver.stats().nodes <= max_nodes),
+                }
+                match solver.solve_min_cost_limited(&budget) {
+                        Outcome::Solved((sol, cost)) => {
+                                assert_eq!(Some (cost), expected.iter().map(|sol| sol.len() as u64).min());
+                                assert!(expected.contains(&normalise(vec![sol]).remove(0)));
+                        }
+                        Outcome::Unsatisfiable  => assert!(expected.is_empty()),
+                        Outcome::Aborted        => assert!(solver.stats().nodes <= max_nodes),
+                }
+                // A budget of exactly the nodes of the whole search is enough.
+                solver.count_solutions(None);
+                let exact = Limits::new().with_max_nodes(solver.stats().nodes);
+                assert_ne!(solver.count_solutions_limited(None, &exact), Outcome::Aborted);
+                // The iterator gives the solutions found before the budget ran out.
+                let mut sols = solver.solutions_limited(budget.clone());
+                let found: Vec<Vec<usize>> = sols.by_ref().collect();
+                assert!(sols.stats().nodes <= max_nodes);
+                if sols.aborted() {
+                        assert!(found.len() <= expected.len());
+                        assert!(normalise(found).iter().all(|sol| expected.contains(sol)));
+                } else {
+                        assert_eq!(normalise(found), expected);
+                }
+                drop(sols);
+
+                let cancelled = Limits::new().with_cancel(Arc::new(AtomicBool::new(true)));
+                assert_eq!(solver.solve_one_limited(&cancelled), Outcome::Aborted);
+                assert_eq!(solver.solve_min_cost_limited(&cancelled), Outcome::Aborted);
+                let expired = Limits::new().with_deadline(Instant::now());
+                assert_eq!(solver.count_solutions_limited(None, &expired), Outcome::Aborted);
+                // Without any row choices, only the empty solution can be found.
+                if let Outcome::Solved(sol) = solver.solve_one_limited(&Limits::new().with_max_nodes(0)) {
+                        assert!(sol.is_empty());
+                }
+                assert_eq!(solver.stats().nodes, 0);
+                // Not searching at all proves nothing.
+                assert_eq!(solver.count_solutions_limited(Some (0), &Limits::new()), Outcome::Solved(0));
+                let want = if expected.is_empty() {Outcome::Unsatisfiable} else {Outcome::Solved(1)};
+                assert_eq!(solver.count_solutions_limited(Some (1), &Limits::new()), want);
+
+                let generous = Limits::new().with_max_nodes(1 << 20);
+                let want = if expected.is_empty() {Outcome::Unsatisfiable} else {Outcome::Solved(expected.clone())};
+                assert_eq!(solver.solve_many_limited(&generous).map(normalise), want);
+
+                // Aborting must have left the solver as it was.
+                assert_eq!(normalise(solver.solve_many()), expected);
+        });
+}
+
+#[test]
+fn stats_and_progress_add_up ()
+{
+        use std::sync::{Arc, Mutex};
+
+        let opts = Opts {bounds: LOOSE, ..OPTS};
+        for_random_matrices(0x510e_527f_ade6_82d1, 500, opts, |m, rng| {
+                let expected = m.brute_force();
+                let mut solver = m.solver();
+
+                let calls = Arc::new(Mutex::new(Vec::new()));
+                let every = NonZeroU64::new(1 + rng.below(3) as u64).unwrap();
+                let calls_cb = calls.clone();
+                solver.set_progress(every, move |depth, rows: &[usize]| {
+                        assert!(rows.len() <= depth);
+                        calls_cb.lock().unwrap().push(depth);
+                });
+                solver.solve_many();
+                let stats = solver.stats().clone();
+                assert_eq!(stats.solutions, expected.len() as u64);
+                assert_eq!(stats.nodes_per_depth.iter().sum::<u64>(), stats.nodes);
+                assert_eq!(stats.max_depth, stats.nodes_per_depth.len());
+                assert!(stats.nodes_per_depth.iter().all(|&n| n > 0));
+                // Without bounds, every choice covers at least its own column, and takes that back later.
+                if m.bounds.iter().all(|&b| b == (1, 1)) {
+                        assert!(stats.updates >= 2 * stats.nodes);
+                }
+                assert_eq!(calls.lock().unwrap().len() as u64, stats.nodes / every);
+
+                solver.clear_progress();
+                let mut sols = solver.solutions();
+                sols.next();
+                let partial = sols.stats().clone();
+                drop(sols);
+                assert!(partial.solutions <= 1);
+                assert_eq!(solver.stats().nodes, partial.nodes);
+                assert_eq!(calls.lock().unwrap().len() as u64, stats.nodes / every);
+
+                assert_eq!(solver.count_solutions(None), expected.len() as u128);
+                assert_eq!(solver.stats(), &stats);
+        });
+}
+
+#[test]
+fn parallel_matches_serial ()
+{
+        let opts = Opts {bounds: LOOSE, ..OPTS};
+        for_random_matrices(0x9b05_688c_2b3e_6c1f, 300, opts, |m, _| {
+                let expected = normalise(m.brute_force());
+                let mut solver = m.solver();
+                let serial = solver.solve_many();
+                let serial_stats = solver.stats().clone();
+                for split_depth in 0..4 {
+                        // With the default heuristics, the threads find the solutions in the same order.
+                        assert_eq!(solver.par_solve_many(split_depth), serial);
+                        assert_eq!(solver.stats().nodes, serial_stats.nodes);
+                        assert_eq!(solver.stats().solutions, serial_stats.solutions);
+                        assert_eq!(solver.par_count(split_depth), expected.len() as u128);
+                        match solver.par_solve_one(split_depth) {
+                                Some (sol) => assert!(expected.contains(&normalise(vec![sol]).remove(0))),
+                                None       => assert!(expected.is_empty()),
+                        }
+                }
+                assert_eq!(solver.solve_many(), serial);
+        });
+}
+
+#[test]
+fn min_cost_is_optimal ()
+{
+        let opts = Opts {bounds: LOOSE, ..OPTS};
+        for_random_matrices(0x1f83_d9ab_fb41_bd6b, 1000, opts, |m, rng| {
+                let costs: Vec<u64> = (0..m.num_rows).map(|_| rng.next() % 10).collect();
+                let total = |sol: &[usize]| sol.iter().map(|&r| costs[r]).sum::<u64>();
+                let expected = m.brute_force();
+                let cheapest = expected.iter().map(|sol| total(sol)).min();
+
+                let row_costs = costs.clone();
+                let mut solver = m.solver().with_row_costs(move |&r| row_costs[r]);
+                let found = solver.solve_min_cost();
+                assert_eq!(found.as_ref().map(|(_, cost)| *cost), cheapest);
+                if let Some((sol, cost)) = &found {
+                        assert_eq!(total(sol), *cost);
+                        assert!(expected.contains(&normalise(vec![sol.clone()]).remove(0)));
+                }
+                assert_eq!(solver.solve_min_cost(), found);
+
+                // Without costs, the fewest rows win.
+                let fewest = expected.iter().map(|sol| sol.len() as u64).min();
+                assert_eq!(m.solver().solve_min_cost().map(|(_, cost)| cost), fewest);
+
+                assert_eq!(normalise(solver.solve_many()), normalise(expected));
+        });
+}
+
+#[test]
+fn min_cost_saturates ()
+{
+        // The only solution costs more than fits in a u64.
+        let rows = [0, 1];
+        let mut solver = UCSolver::from_pred(&rows, &[0, 1], |&r, &c| r == c).with_row_costs(|_| u64::MAX - 1);
+        assert_eq!(solver.solve_min_cost(), Some ((vec![0, 1], u64::MAX)));
+}
+
+#[test]
+fn uniqueness_matches_brute_force ()
+{
+        use dancing_links_x::dlx::Uniqueness;
+
+        for_random_matrices(0x5be0_cd19_137e_2179, 1000, OPTS, |m, rng| {
+                let expected = normalise(m.brute_force());
+                let mut solver = m.solver();
+                match solver.uniqueness() {
+                        Uniqueness::None            => assert!(expected.is_empty()),
+                        Uniqueness::Unique(sol)     => assert_eq!(normalise(vec![sol]), expected),
+                        Uniqueness::Multiple(a, b)  => {
+                                assert!(expected.len() >= 2);
+                                let witnesses = normalise(vec![a, b]);
+                                assert_ne!(witnesses[0], witnesses[1]);
+                                assert!(witnesses.iter().all(|sol| expected.contains(sol)));
+                        }
+                }
+
+                // Forcing a row leaves the solutions that contain it, without it.
+                let forced = rng.below(m.num_rows as u64);
+                if (0..m.num_strict).any(|c| m.get(forced, c)) {
+                        let with_forced: Vec<Vec<usize>> = expected.iter()
+                                .filter(|sol| sol.contains(&forced))
+                                .map(|sol| sol.iter().copied().filter(|&r| r != forced).collect())
+                                .collect();
+                        let uniq = solver.uniqueness_with(&[forced]);
+                        match (uniq, with_forced.len()) {
+                                (Uniqueness::None, 0)           => {}
+                                (Uniqueness::Unique(sol), 1)    => assert_eq!(normalise(vec![sol]), with_forced),
+                                (Uniqueness::Multiple(..), n)   => assert!(n >= 2),
+                                (uniq, n)                       => panic!("{uniq:?} with {n} solutions"),
+                        }
+                }
+                assert_eq!(normalise(solver.solve_many()), expected);
+        });
+}
+
+#[test]
+fn added_and_deleted_rows ()
+{
+        let opts = Opts {rows: (1, 8), strict: (1, 4), bounds: &[(0, 2), (1, 1), (1, 1)], ..OPTS};
+        for_random_matrices(0xcbbb_9d5d_c105_9ed8, 300, opts, |m, rng| {
+                use std::sync::{Arc, Mutex};
+
+                let (num_strict, num_opt, num_cols) = (m.num_strict, m.num_opt, m.num_cols());
+                let cost = |label: usize| (label * 7 % 5) as u64;
+                let mut solver = m.solver().with_row_costs(move |&label| cost(label));
+                // The rows the progress callback is given, which must include added ones.
+                let reported: Arc<Mutex<Vec<Vec<usize>>>> = Arc::new(Mutex::new(Vec::new()));
+                let reported_cb = reported.clone();
+                solver.set_progress(NonZeroU64::MIN, move |_, rows: &[usize]| {
+                        reported_cb.lock().unwrap().push(normalise(vec![rows.to_vec()]).remove(0));
+                });
+
+                // The rows still in the solver: their label, and their cells.
+                let mut live: Vec<(usize, Vec<u8>)> = (0..m.num_rows)
+                        .map(|r| (r, m.cells[r * num_cols..(r + 1) * num_cols].to_vec()))
+                        .collect();
+                let mut next_label = m.num_rows;
+                for _ in 0..6 {
+                        if rng.below(2) == 0 && live.len() > 1 {
+                                let (label, _) = live.remove(rng.below(live.len() as u64));
+                                solver.delete_row(&label);
+                        } else if live.len() < 12 {
+                                let row: Vec<u8> = (0..num_cols).map(|_| u8::from(rng.below(2) == 0)).collect();
+                                if row.iter().all(|&cell| cell == 0) {
+                                        continue;
+                                }
+                                let cols: Vec<usize> = (0..num_cols).filter(|&c| row[c] != 0).collect();
+                                solver.add_row(next_label, &cols);
+                                live.push((next_label, row));
+                                next_label += 1;
+                        }
+
+                        let reference = Matrix {
+                                num_rows: live.len(), num_strict, num_opt,
+                                cells: live.iter().flat_map(|(_, row)| row.iter().copied()).collect(),
+                                bounds: m.bounds.clone(),
+                        };
+                        let expected = normalise(reference.brute_force().into_iter()
+                                .map(|sol| sol.into_iter().map(|r| live[r].0).collect())
+                                .collect());
+                        reported.lock().unwrap().clear();
+                        assert_eq!(normalise(solver.solve_many()), expected);
+                        // The last choice of a solution is told with all of its rows.
+                        let reported = std::mem::take(&mut *reported.lock().unwrap());
+                        assert!(expected.iter().filter(|sol| !sol.is_empty()).all(|sol| reported.contains(sol)));
+                        assert_eq!(solver.count_solutions(None), expected.len() as u128);
+                        let cheapest = expected.iter().map(|sol| sol.iter().map(|&label| cost(label)).sum::<u64>()).min();
+                        assert_eq!(solver.solve_min_cost().map(|(_, total)| total), cheapest);
+                        if let Some(&(label, _)) = live.first() {
+                                let with_first = expected.iter().filter(|sol| sol.contains(&label)).count();
+                                let has_strict = live[0].1[..num_strict].iter().any(|&cell| cell != 0);
+                                if has_strict {
+                                        assert_eq!(solver.count_solutions_with(&[label], None), with_first as u128);
+                                }
+                        }
+                }
+        });
+}
+
+#[test]
+fn added_colored_rows ()
+{
+        // The first rows may have no colors at all, so the colors only come with the added rows.
+        let opts = Opts {opt: (1, 3), density: (2, 4), colors: 2, some_colored: true, ..OPTS};
+        for_random_matrices(0x3c6e_f372_fe94_f82b, 500, opts, |m, rng| {
+                let num_cols = m.num_cols();
+                let num_built = 1 + rng.below(m.num_rows as u64);
+                let built = Matrix {
+                        num_rows: num_built, num_strict: m.num_strict, num_opt: m.num_opt,
+                        cells: m.cells[..num_built * num_cols].to_vec(),
+                        bounds: m.bounds.clone(),
+                };
+                let mut solver = built.solver();
+                for r in num_built..m.num_rows {
+                        let cols: Vec<(usize, Option<Color>)> = (0..num_cols)
+                                .filter(|&c| m.get(r, c))
+                                .map(|c| (c, m.color(r, c)))
+                                .collect();
+                        solver.add_colored_row(r, &cols);
+                }
+                assert_eq!(normalise(solver.solve_many()), normalise(m.brute_force()));
+        });
+}
+
+#[test]
+fn added_columns ()
+{
+        use dancing_links_x::dlx::ColumnKind;
+
+        let opts = Opts {rows: (1, 8), strict: (1, 3), ..OPTS};
+        for_random_matrices(0x243f_6a88_85a3_08d3, 300, opts, |m, rng| {
+                let num_cols = m.num_cols();
+                let mut solver = m.solver();
+
+                // Whether each column of the solver is strict, and the cells of each row, in solver column order.
+                let mut strict: Vec<bool> = (0..num_cols).map(|c| c < m.num_strict).collect();
+                let mut rows: Vec<Vec<u8>> = (0..m.num_rows).map(|r| m.cells[r * num_cols..(r + 1) * num_cols].to_vec()).collect();
+                for _ in 0..5 {
+                        if rng.below(2) == 0 {
+                                let is_strict = rng.below(2) == 0;
+                                let members: Vec<usize> = (0..rows.len()).filter(|_| rng.below(3) == 0).collect();
+                                let kind = if is_strict {ColumnKind::Strict} else {ColumnKind::Optional};
+                                assert_eq!(solver.add_column(kind, &members), strict.len());
+                                strict.push(is_strict);
+                                for (r, row) in rows.iter_mut().enumerate() {
+                                        row.push(u8::from(members.contains(&r)));
+                                }
+                        } else {
+                                let row: Vec<u8> = (0..strict.len()).map(|_| u8::from(rng.below(2) == 0)).collect();
+                                if row.iter().all(|&cell| cell == 0) {
+                                        continue;
+                                }
+                                let cols: Vec<usize> = (0..strict.len()).filter(|&c| row[c] != 0).collect();
+                                solver.add_row(rows.len(), &cols);
+                                rows.push(row);
+                        }
+
+                        // The reference has the strict columns first.
+                        let order: Vec<usize> = (0..strict.len()).filter(|&c| strict[c])
+                                .chain((0..strict.len()).filter(|&c| !strict[c]))
+                                .collect();
+                        let ref_strict = strict.iter().filter(|&&s| s).count();
+                        let reference = Matrix {
+                                num_rows: rows.len(),
+                                num_strict: ref_strict,
+                                num_opt: strict.len() - ref_strict,
+                                cells: rows.iter().flat_map(|row| order.iter().map(|&c| row[c])).collect(),
+                                bounds: vec![(1, 1); ref_strict],
+                        };
+                        let expected = normalise(reference.brute_force());
+                        assert_eq!(normalise(solver.solve_many()), expected);
+                        assert_eq!(solver.count_solutions(None), expected.len() as u128);
+                }
+        });
+}
+
+#[test]
+fn knuth_format_round_trip ()
+{
+        let opts = Opts {rows: (1, 10), strict: (1, 4), colors: 2, some_colored: true, bounds: LOOSE, ..OPTS};
+        for_random_matrices(0x1319_8a2e_0370_7344, 500, opts, |m, _| {
+                // Identical rows give identical options, which cannot be told apart below.
+                let width = m.num_cols();
+                let row = |r: usize| &m.cells[r * width..(r + 1) * width];
+                if (0..m.num_rows).any(|a| (0..a).any(|b| row(a) == row(b))) {
+                        return;
+                }
+                let text = m.solver().to_dlx_format().unwrap();
+                let mut parsed = UCSolver::from_dlx_format(&text).unwrap();
+                // The rows are written in order, one per line after the items.
+                let options: Vec<&str> = text.lines().skip(1).collect();
+                assert_eq!(options.len(), m.num_rows);
+                let sols = parsed.solve_many().into_iter()
+                        .map(|sol| sol.iter().map(|row| options.iter().position(|o| o == row).unwrap()).collect())
+                        .collect();
+                assert_eq!(normalise(sols), normalise(m.brute_force()));
+                // Writing what was read gives the same text.
+                assert_eq!(parsed.to_dlx_format().unwrap(), text);
+        });
+}
+
+// Whether a CNF is satisfiable, by unit propagation and branching on the first open variable.
+// Good enough for the few dozen variables of the encodings below.
+fn satisfiable (clauses: &[Vec<i64>], assignment: &mut Vec<Option<bool>>) -> bool
+{
+        let value = |assignment: &[Option<bool>], lit: i64| assignment[lit.unsigned_abs() as usize].map(|v| v == (lit > 0));
+        let mut trail = Vec::new();
+        let result = loop {
+                let mut changed = false;
+                let mut conflict = false;
+                for clause in clauses {
+                        if clause.iter().any(|&lit| value(assignment, lit) == Some(true)) {
+                                continue;
+                        }
+                        let open: Vec<i64> = clause.iter().copied().filter(|&lit| value(assignment, lit).is_none()).collect();
+                        match open.len() {
+                                0 => {conflict = true; break}
+                                1 => {
+                                        assignment[open[0].unsigned_abs() as usize] = Some (open[0] > 0);
+                                        trail.push(open[0].unsigned_abs() as usize);
+                                        changed = true;
+                                }
+                                _ => {}
+                        }
+                }
+                if conflict {
+                        break false;
+                }
+                if changed {
+                        continue;
+                }
+                let Some(var) = (1..assignment.len()).find(|&v| assignment[v].is_none()) else {
+                        break true;
+                };
+                break [true, false].into_iter().any(|v| {
+                        assignment[var] = Some (v);
+                        let sat = satisfiable(clauses, assignment);
+                        assignment[var] = None;
+                        sat
+                });
+        };
+        for var in trail {
+                assignment[var] = None;
+        }
+        result
+}
+
+// Small instances for the exports, whose checks try every subset of rows,
+// with all kinds of bounds the encodings treat differently.
+const EXPORT_OPTS: Opts = Opts {
+        rows:           (1, 7),
+        strict:         (1, 3),
+        density:        (2, 2),
+        colors:         2,
+        some_colored:   true,
+        bounds:         &[(1, 1), (0, 1), (0, 2), (1, 3), (2, 2)],
+        ..OPTS
+};
+
+#[test]
+fn cnf_matches_brute_force ()
+{
+        use dancing_links_x::dlx::CnfEncoding;
+
+        for_random_matrices(0x082e_fa98_ec4e_6c89, 300, EXPORT_OPTS, |m, _| {
+                let num_rows = m.num_rows;
+                let expected = normalise(m.brute_force());
+                let solver = m.solver();
+                for encoding in [CnfEncoding::Pairwise, CnfEncoding::Sequential, CnfEncoding::Commander] {
+                        let mut out = Vec::new();
+                        let rows = solver.to_cnf(&mut out, encoding).unwrap();
+                        assert_eq!(rows, (0..num_rows).collect::<Vec<_>>());
+                        let text = String::from_utf8(out).unwrap();
+                        let mut lines = text.lines().filter(|line| !line.starts_with('c'));
+                        let header: Vec<usize> = lines.next().unwrap().split_whitespace().skip(2).map(|n| n.parse().unwrap()).collect();
+                        let mut clauses: Vec<Vec<i64>> = lines.map(|line| {
+                                let mut clause: Vec<i64> = line.split_whitespace().map(|lit| lit.parse().unwrap()).collect();
+                                assert_eq!(clause.pop(), Some(0));
+                                clause
+                        }).collect();
+                        assert_eq!(clauses.len(), header[1]);
+
+                        // The models, restricted to the row variables, are exactly the solutions.
+                        let mut models = Vec::new();
+                        for subset in 0u32..(1 << num_rows) {
+                                let units = (0..num_rows).map(|r| if subset & (1 << r) != 0 {r as i64 + 1} else {-(r as i64 + 1)});
+                                let num_clauses = clauses.len();
+                                clauses.extend(units.map(|lit| vec![lit]));
+                                if satisfiable(&clauses, &mut vec![None; header[0] + 1]) {
+                                        models.push((0..num_rows).filter(|r| subset & (1 << r) != 0).collect());
+                                }
+                                clauses.truncate(num_clauses);
+                        }
+                        assert_eq!(normalise(models), expected, "{encoding:?}");
+                }
+        });
+}
+
+// A constraint of an MPS file: its sense, coefficients by variable, right hand side and range.
+type MpsConstraint = (char, Vec<(usize, i64)>, i64, Option<i64>);
+
+// The constraints of a free MPS file, to check which assignments it allows.
+struct Mps {
+        vars:           Vec<String>,
+        fixed_zero:     Vec<bool>,
+        constraints:    Vec<MpsConstraint>,
+}
+
+impl Mps {
+        fn parse (text: &str) -> Mps
+        {
+                let mut mps = Mps {vars: Vec::new(), fixed_zero: Vec::new(), constraints: Vec::new()};
+                let mut con_names: Vec<String> = Vec::new();
+                let mut section = "";
+                for line in text.lines().filter(|line| !line.starts_with('*')) {
+                        let toks: Vec<&str> = line.split_whitespace().collect();
+                        if !line.starts_with(' ') {
+                                section = toks[0];
+                                continue;
+                        }
+                        let con = |name: &str| con_names.iter().position(|c| c == name);
+                        match section {
+                                "ROWS" if toks[1] != "obj" => {
+                                        mps.constraints.push((toks[0].chars().next().unwrap(), Vec::new(), 0, None));
+                                        con_names.push(toks[1].to_string());
+                                }
+                                "COLUMNS" if toks[0] != "MARKER" => {
+                                        if mps.vars.last().map(String::as_str) != Some(toks[0]) {
+                                                mps.vars.push(toks[0].to_string());
+                                                mps.fixed_zero.push(false);
+                                        }
+                                        if let Some(c) = con(toks[1]) {
+                                                mps.constraints[c].1.push((mps.vars.len() - 1, toks[2].parse().unwrap()));
+                                        }
+                                }
+                                "RHS"    => mps.constraints[con(toks[1]).unwrap()].2 = toks[2].parse().unwrap(),
+                                "RANGES" => mps.constraints[con(toks[1]).unwrap()].3 = Some(toks[2].parse().unwrap()),
+                                "BOUNDS" => {
+                                        let var = mps.vars.iter().position(|v| v == toks[2]).unwrap();
+                                        mps.fixed_zero[var] = toks[0] == "FX";
+                                }
+                                _ => {}
+                        }
+                }
+                mps
+        }
+
+        fn feasible (&self, values: &[i64]) -> bool
+        {
+                let fixed_ok = values.iter().zip(&self.fixed_zero).all(|(&v, &fixed)| !fixed || v == 0);
+                fixed_ok && self.constraints.iter().all(|(sense, terms, rhs, range)| {
+                        let sum: i64 = terms.iter().map(|&(var, coef)| coef * values[var]).sum();
+                        match sense {
+                                'E' => sum == *rhs,
+                                'L' => sum <= *rhs,
+                                'G' => sum >= *rhs && range.is_none_or(|range| sum <= rhs + range),
+                                _   => panic!("Unknown sense {sense}"),
+                        }
+                })
+        }
+}
+
+#[test]
+fn mps_matches_brute_force ()
+{
+        for_random_matrices(0x4528_21e6_38d0_1377, 300, EXPORT_OPTS, |m, _| {
+                let num_rows = m.num_rows;
+                let expected = normalise(m.brute_force());
+                let solver = m.solver();
+                let (text, rows) = solver.to_mps(|&r| r as f64);
+                assert_eq!(rows, (0..num_rows).collect::<Vec<_>>());
+                let mps = Mps::parse(&text);
+                assert_eq!(&mps.vars[..num_rows], (0..num_rows).map(|r| format!("x{r}")).collect::<Vec<_>>());
+
+                // A set of rows is feasible when some values of the color variables make it so,
+                // and it suffices to switch on the colors that the chosen rows use.
+                let mut feasible = Vec::new();
+                for subset in 0u32..(1 << num_rows) {
+                        let chosen: Vec<usize> = (0..num_rows).filter(|r| subset & (1 << r) != 0).collect();
+                        let values: Vec<i64> = mps.vars.iter().enumerate().map(|(var, name)| {
+                                if var < num_rows {
+                                        return i64::from(chosen.contains(&var));
+                                }
+                                let (col, color) = name[1..].split_once('_').unwrap();
+                                let (col, color): (usize, u8) = (col.parse().unwrap(), color.parse().unwrap());
+                                i64::from(chosen.iter().any(|&r| m.cell(r, col) == color + 1))
+                        }).collect();
+                        if mps.feasible(&values) {
+                                feasible.push(chosen);
+                        }
+                }
+                assert_eq!(normalise(feasible), expected);
+        });
+}
+
+#[test]
+fn row_fn_matches_brute_force ()
+{
+        let opts = Opts {rows: (1, 10), strict: (1, 4), ..OPTS};
+        for_random_matrices(0xbe54_66cf_34e9_0c6c, 500, opts, |m, _| {
+                let expected = normalise(m.brute_force());
+                let rows: Vec<usize> = (0..m.num_rows).collect();
+                let strict_cols: Vec<usize> = (0..m.num_strict).collect();
+                let opt_cols: Vec<usize> = (m.num_strict..m.num_cols()).collect();
+                // Give the columns backwards, to check that their order does not matter.
+                let row_cols = |&r: &usize| (0..m.num_cols()).rev().filter(move |&c| m.get(r, c));
+
+                let mut solver = UCSolver::from_row_fn_opt(&rows, &strict_cols, &opt_cols, row_cols);
+                assert_eq!(normalise(solver.solve_many()), expected);
+                let names = |c: usize| format!("col {c}");
+                let strict_names: Vec<String> = strict_cols.iter().map(|&c| names(c)).collect();
+                let opt_names: Vec<String> = opt_cols.iter().map(|&c| names(c)).collect();
+                let mut solver = UCSolver::from_row_vals(&rows, &strict_names, &opt_names, |r| row_cols(r).map(names));
+                assert_eq!(normalise(solver.solve_many()), expected);
+                if m.num_opt == 0 {
+                        let mut solver = UCSolver::from_row_fn(&rows, &strict_cols, row_cols);
+                        assert_eq!(normalise(solver.solve_many()), expected);
+                }
+        });
+}
+
+#[test]
+fn hashed_from_it_matches_brute_force ()
+{
+        use dancing_links_x::dlx::{ColumnKind, DuplicatePair};
+
+        let opts = Opts {rows: (1, 10), strict: (1, 4), empty_columns: false, ..OPTS};
+        for_random_matrices(0x3f84_d5b5_b547_0917, 500, opts, |m, rng| {
+                let expected = normalise(m.brute_force());
+
+                // The entries in a random order, so that strict and optional columns are mixed.
+                let mut entries: Vec<(usize, usize)> = (0..m.num_rows)
+                        .flat_map(|r| (0..m.num_cols()).map(move |c| (r, c)))
+                        .filter(|&(r, c)| m.get(r, c))
+                        .collect();
+                for i in (1..entries.len()).rev() {
+                        entries.swap(i, rng.below(i as u64 + 1));
+                }
+                let kind = |&c: &usize| if c < m.num_strict {ColumnKind::Strict} else {ColumnKind::Optional};
+                let mut solver = UCSolver::from_it_hashed(entries.iter().copied(), kind).unwrap();
+                assert_eq!(normalise(solver.solve_many()), expected);
+
+                let dup = entries[rng.below(entries.len() as u64)];
+                entries.insert(rng.below(entries.len() as u64 + 1), dup);
+                let err = UCSolver::from_it_hashed(entries.iter().copied(), kind).err().unwrap();
+                assert_eq!(err, DuplicatePair {row: dup.0, col: dup.1});
+                assert_eq!(err.to_string(), format!("Row {} is given with column {} more than once", dup.0, dup.1));
+        });
+}
@@ -0,0 +1,217 @@
+// The try_ methods report misuse as a DlxError, and leave the solver as it was.
+
+use dancing_links_x::dlx::{Color, ColumnKind, DlxError, EmptyRows, UCSolver};
+
+fn small () -> UCSolver<usize>
+{
+        // Rows 0 and 1 together cover both columns, as does row 2 on its own.
+        let rows: Vec<usize> = (0..3).collect();
+        UCSolver::from_pred(&rows, &[0, 1], |&r, &c| r == c || r == 2)
+}
+
+#[test]
+fn constructors ()
+{
+        let rows = [0, 1];
+        let err = UCSolver::try_from_pred(&rows, &[0, 1], |&r, _| r == 0, EmptyRows::Reject).err();
+        assert_eq!(err, Some (DlxError::EmptyRow {row: 1}));
+
+        let err = UCSolver::try_from_pred_bounded(&rows, &[0, 1], &[(1, 1)], &[], |&r, &c| r == c, EmptyRows::Reject).err();
+        assert_eq!(err, Some (DlxError::WrongNumberOfBounds {expected: 2, given: 1}));
+        let err = UCSolver::try_from_pred_bounded(&rows, &[0, 1], &[(1, 1), (2, 1)], &[], |&r, &c| r == c, EmptyRows::Reject).err();
+        assert_eq!(err, Some (DlxError::InvalidBounds {col: 1, lo: 2, hi: 1}));
+        let err = small().with_bounds(&[(1, 1), (1, 1), (1, 1)]).err();
+        assert_eq!(err, Some (DlxError::WrongNumberOfBounds {expected: 2, given: 3}));
+        let err = small().with_bounds(&[(0, 0), (1, 1)]).err();
+        assert_eq!(err, Some (DlxError::InvalidBounds {col: 0, lo: 0, hi: 0}));
+        let mut solver = small();
+        solver.set_state(&[0]);
+        assert_eq!(solver.with_bounds(&[(1, 1), (1, 1)]).err(), Some (DlxError::RowsRemoved));
+        assert_eq!(small().with_bounds(&[(0, 1), (1, 1)]).unwrap().count_solutions(None), 3);
+
+        let err = UCSolver::try_from_row_vals(&rows, &['a', 'b'], &[], |&r| if r == 0 {vec!['a']} else {vec!['c']}, EmptyRows::Reject).err();
+        assert_eq!(err, Some (DlxError::UnknownColumn {row: 1}));
+        let err = UCSolver::try_from_row_vals(&rows, &['a', 'b'], &['a'], |_| vec!['a'], EmptyRows::Reject).err();
+        assert_eq!(err, Some (DlxError::DuplicateColumn {col: 2}));
+
+        let err = UCSolver::try_from_it([(0, 'a'), (1, 'b'), (1, 'b')]).err();
+        assert_eq!(err, Some (DlxError::DuplicateEntry {row: 1, col: 1}));
+        assert!(UCSolver::<usize>::try_from_it([(0, 'a'), (1, 'b')]).is_ok());
+
+        let color = |_: &usize, _: &i32| Color::new(0);
+        assert!(UCSolver::try_from_pred_colored(&rows, &[0, 1], &[2], |&r, &c| r == c as usize || c == 2, color, EmptyRows::Reject).is_ok());
+}
+
+#[test]
+fn changes ()
+{
+        let mut solver = small();
+        assert_eq!(solver.try_add_row(1, &[0]), Err (DlxError::RowExists));
+        assert_eq!(solver.try_add_row(3, &[]), Err (DlxError::EmptyRow {row: 3}));
+        assert_eq!(solver.try_add_row(3, &[0, 2]), Err (DlxError::ColumnOutOfRange {row: 3, col: 2}));
+        assert_eq!(solver.try_add_row(3, &[1, 0, 1]), Err (DlxError::DuplicateEntry {row: 3, col: 1}));
+        let red = Color::new(1);
+        assert_eq!(solver.try_add_colored_row(3, &[(0, red)]), Err (DlxError::ColoredStrictColumn {row: 3, col: 0}));
+        assert_eq!(solver.try_add_column(ColumnKind::Optional, &[7]), Err (DlxError::UnknownRow));
+        assert_eq!(solver.try_delete_row(&7), Err (DlxError::UnknownRow));
+        assert_eq!(solver.count_solutions(None), 2);
+
+        assert_eq!(solver.try_set_state(&[0]), Ok (1));
+        assert_eq!(solver.try_add_row(3, &[0]), Err (DlxError::RowsRemoved));
+        assert_eq!(solver.try_add_column(ColumnKind::Strict, &[]), Err (DlxError::RowsRemoved));
+        assert_eq!(solver.try_delete_row(&1), Err (DlxError::RowsRemoved));
+        assert_eq!(solver.try_recover_n(2), Err (DlxError::NothingToRecover {requested: 2, removed: 1}));
+        assert_eq!(solver.try_recover_n(1), Ok (()));
+        assert_eq!(solver.try_recover_n(1), Err (DlxError::NothingToRecover {requested: 1, removed: 0}));
+
+        assert_eq!(solver.try_add_row(3, &[1]), Ok (()));
+        assert_eq!(solver.try_delete_row(&1), Ok (()));
+        assert_eq!(solver.try_delete_row(&1), Err (DlxError::UnknownRow));
+        assert_eq!(solver.count_solutions(None), 2);
+}
+
+#[test]
+fn set_state_is_all_or_nothing ()
+{
+        let mut solver = small();
+        assert_eq!(solver.try_set_state(&[0, 7]), Err (DlxError::UnknownRow));
+        assert_eq!(solver.try_set_state(&[0, 0]), Err (DlxError::RowAlreadyRemoved));
+        assert_eq!(solver.try_set_state(&[0, 2]), Err (DlxError::RowConflicts));
+        assert_eq!(solver.try_recover_n(1), Err (DlxError::NothingToRecover {requested: 1, removed: 0}));
+        assert_eq!(solver.count_solutions(None), 2);
+
+        // Forced rows that conflict leave no solution.
+        assert_eq!(solver.solve_one_with(&[0, 2]), None);
+        assert_eq!(solver.count_solutions_with(&[0, 2], None), 0);
+        assert_eq!(solver.count_solutions_with(&[0], None), 1);
+
+        // Forced rows that do not exist, or are given twice, are an error.
+        assert_eq!(solver.try_solve_one_with(&[7]), Err (DlxError::UnknownRow));
+        assert_eq!(solver.try_solve_many_with(&[0, 0]), Err (DlxError::RowAlreadyRemoved));
+        assert!(matches!(solver.try_uniqueness_with(&[1, 7]), Err (DlxError::UnknownRow)));
+        assert_eq!(solver.try_count_solutions_with(&[0, 2], None), Ok (0));
+        assert_eq!(solver.try_solve_one_with(&[2]), Ok (Some (vec![])));
+        assert_eq!(solver.count_solutions(None), 2);
+}
+
+#[test]
+fn conflicts_respect_bounds_and_colors ()
+{
+        // Column 0 may be covered twice, by any of the rows.
+        let rows: Vec<usize> = (0..3).collect();
+        let mut solver = UCSolver::from_pred_bounded(&rows, &[0], &[(1, 2)], &[], |_, _| true);
+        assert_eq!(solver.try_set_state(&[0, 1]), Ok (2));
+        assert_eq!(solver.try_set_state(&[2]), Err (DlxError::RowConflicts));
+        solver.recover_n(2);
+
+        // Rows 0 and 1 agree on the color of column 2, row 2 does not.
+        let mut solver = UCSolver::from_pred_colored(&rows, &[0, 1], &[2], |&r, &c| c == 2 || r == c as usize,
+                        |&r, _| Color::new(if r == 2 {2} else {1}));
+        assert_eq!(solver.try_set_state(&[0, 1]), Ok (2));
+        assert_eq!(solver.try_set_state(&[2]), Err (DlxError::RowConflicts));
+        assert_eq!(solver.count_solutions(None), 1);
+}
+
+#[test]
+fn empty_columns ()
+{
+        // No row covers strict column 2, so it can not be covered.
+        let rows = [0, 1];
+        let mut solver = UCSolver::try_from_pred(&rows, &[0, 1, 2], |&r, &c| r == c, EmptyRows::Reject).unwrap();
+        assert_eq!(solver.count_solutions(None), 0);
+        assert_eq!(solver.solve_one(), None);
+
+        // But an optional one may stay uncovered.
+        let mut solver = UCSolver::try_from_pred_opt(&rows, &[0, 1], &[2], |&r, &c| r == c, EmptyRows::Reject).unwrap();
+        assert_eq!(solver.count_solutions(None), 1);
+
+        // Until a row covers it.
+        let mut solver = UCSolver::from_pred(&rows, &[0, 1, 2], |&r, &c| r == c);
+        solver.add_row(2, &[2]);
+        assert_eq!(solver.count_solutions(None), 1);
+        let col = solver.add_column(ColumnKind::Strict, &[]);
+        assert_eq!(col, 3);
+        assert_eq!(solver.count_solutions(None), 0);
+}
+
+#[test]
+fn empty_rows ()
+{
+        let mut solver = small();
+        assert_eq!(solver.try_add_row(3, &[]), Err (DlxError::EmptyRow {row: 3}));
+        solver.set_empty_rows(EmptyRows::Ignore);
+        assert_eq!(solver.try_add_row(3, &[]), Ok (()));
+        assert_eq!(solver.count_solutions(None), 2);
+        // Forcing the row changes nothing, but it counts as removed until recovered.
+        assert_eq!(solver.try_set_state(&[3, 0]), Ok (2));
+        assert_eq!(solver.count_solutions(None), 1);
+        assert_eq!(solver.try_set_state(&[3]), Err (DlxError::RowAlreadyRemoved));
+        solver.recover_n(2);
+        assert_eq!(solver.solve_many_with(&[3]).len(), 2);
+        assert_eq!(solver.try_count_solutions_with(&[2, 3], None), Ok (1));
+        // It can not be given a column, but it can be deleted.
+        assert_eq!(solver.try_add_column(ColumnKind::Optional, &[1, 3]), Err (DlxError::EmptyRowInColumn {row: 3}));
+        solver.delete_row(&3);
+        assert_eq!(solver.try_set_state(&[3]), Err (DlxError::UnknownRow));
+        assert_eq!(solver.try_delete_row(&3), Err (DlxError::UnknownRow));
+        assert_eq!(solver.count_solutions(None), 2);
+        // Rows added afterwards still give their own data.
+        solver.delete_row(&0);
+        solver.add_row(4, &[0]);
+        solver.add_row(5, &[]);
+        solver.add_row(6, &[1]);
+        let mut sols: Vec<Vec<usize>> = solver.solve_many().into_iter()
+                .map(|mut sol| {sol.sort(); sol})
+                .collect();
+        sols.sort();
+        assert_eq!(sols, [vec![1, 4], vec![2], vec![4, 6]]);
+
+        // Every row, ignored or not, gets a cost.
+        let mut solver = solver.with_row_costs(|&r| r as u64);
+        assert_eq!(solver.solve_min_cost(), Some ((vec![2], 2)));
+}
+
+#[test]
+fn constructors_ignore_empty_rows_if_asked ()
+{
+        // Row 1 covers nothing, and row 3 only its column.
+        let rows = [0, 1, 2, 3];
+        let cols = |&r: &usize| match r {
+                0 => vec![0],
+                1 => vec![],
+                2 => vec![1],
+                _ => vec![0, 1],
+        };
+        let err = UCSolver::try_from_row_fn(&rows, &['a', 'b'], cols, EmptyRows::Reject).err();
+        assert_eq!(err, Some (DlxError::EmptyRow {row: 1}));
+
+        let mut solver = UCSolver::try_from_row_fn(&rows, &['a', 'b'], cols, EmptyRows::Ignore).unwrap();
+        let mut sols = solver.solve_many();
+        sols.sort();
+        assert_eq!(sols, [vec![0, 2], vec![3]]);
+        assert_eq!(solver.try_set_state(&[1]), Ok (1));
+        assert_eq!(solver.count_solutions(None), 2);
+        solver.recover_n(1);
+        // And so does add_row from then on.
+        assert_eq!(solver.try_add_row(4, &[]), Ok (()));
+        solver.add_row(5, &[1]);
+        assert_eq!(solver.count_solutions(None), 3);
+
+        let solver = UCSolver::try_from_pred(&rows, &[0, 1], |&r, &c| cols(&r).contains(&c), EmptyRows::Ignore).unwrap();
+        assert_eq!(solver.with_row_costs(|&r| r as u64).solve_min_cost(), Some ((vec![0, 2], 2)));
+}
+
+#[test]
+fn messages ()
+{
+        assert_eq!(DlxError::EmptyRow {row: 4}.to_string(), "Row 4 covers no column");
+        assert_eq!(DlxError::UnknownRow.to_string(), "Tried to use a non-existent row");
+        assert_eq!(DlxError::EmptyRowInColumn {row: 3}.to_string(), "Tried to add ignored empty row 3 to a column");
+}
+
+#[test]
+#[should_panic(expected = "Row 1 covers no column")]
+fn panicking_constructors_give_the_message ()
+{
+        UCSolver::from_pred(&[0, 1], &[0], |&r, _| r == 0);
+}
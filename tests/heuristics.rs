@@ -0,0 +1,170 @@
+// Column choosers and row orders, on a small instance where the choices can be followed by hand.
+
+use std::sync::{Arc, Mutex};
+
+use dancing_links_x::dlx::{ColumnChooser, ColumnInfo, Leftmost, Mrv, MrvRandom, Priority, Rows, UCSolver};
+
+// Columns a, b and c have 2, 3 and 3 rows. The five solutions are
+// {0, 1}, {0, 5}, {1, 2, 3}, {2, 3, 5} and {2, 4}.
+const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+
+fn solver () -> UCSolver<&'static str>
+{
+        UCSolver::from_pred(&ROWS, &['a', 'b', 'c'], |r, &c| r.contains(c))
+}
+
+fn sorted (mut sols: Vec<Vec<&'static str>>) -> Vec<Vec<&'static str>>
+{
+        for sol in &mut sols {
+                sol.sort();
+        }
+        sols.sort();
+        sols
+}
+
+// Runs the search with the chooser, and returns the columns it was shown
+// and picked on its first call.
+fn first_choice <H: ColumnChooser + Send + Sync + 'static> (mut chooser: H) -> (Vec<ColumnInfo>, usize)
+{
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let mut solver = solver();
+        solver.set_column_chooser(move |cols: &[ColumnInfo]| {
+                let pick = chooser.choose(cols);
+                log.lock().unwrap().push((cols.to_vec(), pick));
+                pick
+        });
+        assert_eq!(sorted(solver.solve_many()), sorted(self::solver().solve_many()));
+        let seen = seen.lock().unwrap();
+        seen[0].clone()
+}
+
+fn info (index: usize, size: u64) -> ColumnInfo
+{
+        ColumnInfo {index, size, branches: size}
+}
+
+#[test]
+fn all_choosers_find_the_same_solutions ()
+{
+        let expected = vec![vec!["a", "b", "c"], vec!["a", "b", "c"], vec!["a", "bc"], vec!["ab", "c"], vec!["ab", "c"]];
+        assert_eq!(sorted(solver().solve_many()), expected);
+        for seed in 0..4 {
+                let mut solver = solver();
+                solver.set_column_chooser(MrvRandom::new(seed));
+                assert_eq!(sorted(solver.solve_many()), expected);
+        }
+}
+
+#[test]
+fn first_choices ()
+{
+        let all = vec![info(0, 2), info(1, 3), info(2, 3)];
+        assert_eq!(first_choice(Mrv), (all.clone(), 0));
+        assert_eq!(first_choice(Leftmost), (all.clone(), 0));
+        assert_eq!(first_choice(Priority::new(&[0, 0, 5])), (all.clone(), 2));
+        // Equal priorities fall back to the fewest branches.
+        assert_eq!(first_choice(Priority::new(&[0, 1, 1])), (all.clone(), 1));
+        assert_eq!(first_choice(|cols: &[ColumnInfo]| cols.len() - 1), (all, 2));
+}
+
+#[test]
+fn chooser_sees_only_uncovered_columns ()
+{
+        // After branching on a with row "ab", only c is left.
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let mut solver = solver();
+        solver.set_column_chooser(move |cols: &[ColumnInfo]| {
+                log.lock().unwrap().push(cols.iter().map(|col| col.index).collect::<Vec<_>>());
+                0
+        });
+        solver.solve_one_with(&["ab"]);
+        assert_eq!(*seen.lock().unwrap(), [vec![2]]);
+}
+
+// The same instance, with the row indices as the rows.
+fn index_solver () -> UCSolver<usize>
+{
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c))
+}
+
+fn in_order_found (solver: &mut UCSolver<usize>) -> Vec<Vec<usize>>
+{
+        solver.solutions().map(|mut sol| {
+                sol.sort();
+                sol
+        }).collect()
+}
+
+#[test]
+fn row_order_by_key ()
+{
+        let mut solver = index_solver();
+        solver.set_column_chooser(Leftmost);
+        assert_eq!(in_order_found(&mut solver), [vec![0, 1], vec![0, 5], vec![1, 2, 3], vec![2, 3, 5], vec![2, 4]]);
+
+        // Later rows first turns the whole search around.
+        solver.set_row_order_by_key(|&r| std::cmp::Reverse(r));
+        assert_eq!(in_order_found(&mut solver), [vec![2, 4], vec![2, 3, 5], vec![1, 2, 3], vec![0, 5], vec![0, 1]]);
+        assert_eq!(solver.solve_one().map(|mut sol| {
+                sol.sort();
+                sol
+        }), Some (vec![2, 4]));
+}
+
+#[test]
+fn row_order_sees_the_branching_column ()
+{
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let mut solver = index_solver();
+        solver.set_column_chooser(Leftmost);
+        solver.set_row_order(move |col: usize, rows: &mut Rows| {
+                log.lock().unwrap().push((col, rows.iter().map(|row| row.index).collect::<Vec<_>>()));
+                rows.reverse();
+        });
+        assert_eq!(solver.solve_one().map(|sol| sol.len()), Some (2));
+        // Column a has rows 0 and 2, and after row 2 column b has rows 3 and 4.
+        assert_eq!(*seen.lock().unwrap(), [(0, vec![0, 2]), (1, vec![3, 4])]);
+}
+
+#[test]
+fn random_solutions ()
+{
+        let mut solver = index_solver();
+        let all = [vec![0, 1], vec![0, 5], vec![1, 2, 3], vec![2, 3, 5], vec![2, 4]];
+        let mut firsts = Vec::new();
+        for seed in 0..64 {
+                let mut sol = solver.solve_random(seed).unwrap();
+                sol.sort();
+                assert!(all.contains(&sol));
+                let mut again = solver.solve_random(seed).unwrap();
+                again.sort();
+                assert_eq!(sol, again);
+                firsts.push(sol);
+
+                let mut sols: Vec<_> = solver.solutions_random(seed).map(|mut sol| {
+                        sol.sort();
+                        sol
+                }).collect();
+                sols.sort();
+                assert_eq!(sols, all);
+        }
+        // Every solution comes first for some seed.
+        firsts.sort();
+        firsts.dedup();
+        assert_eq!(firsts, all);
+}
+
+#[test]
+fn random_search_leaves_the_heuristics ()
+{
+        let mut solver = index_solver();
+        solver.set_column_chooser(Leftmost);
+        solver.set_row_order_by_key(|&r| std::cmp::Reverse(r));
+        solver.solve_random(7);
+        drop(solver.solutions_random(8));
+        assert_eq!(in_order_found(&mut solver), [vec![2, 4], vec![2, 3, 5], vec![1, 2, 3], vec![0, 5], vec![0, 1]]);
+}
@@ -0,0 +1,97 @@
+// Taking solutions one at a time or the search step by step, and stopping halfway.
+
+use dancing_links_x::dlx::{Step, UCSolver};
+
+fn sorted (mut sol: Vec<usize>) -> Vec<usize>
+{
+        sol.sort();
+        sol
+}
+
+#[test]
+fn solutions_one_at_a_time ()
+{
+        // Rows 0 and 1 together cover both columns, as does row 2 on its own.
+        let rows = [0, 1, 2];
+        let mut solver = UCSolver::from_pred(&rows, &['a', 'b'], |&r, &c| r == 2 || (r == 0) == (c == 'a'));
+        let mut sols = solver.solutions();
+        let first = sols.next().unwrap();
+        let second = sols.next().unwrap();
+        assert_eq!(sols.next(), None);
+        assert_eq!(sols.next(), None);
+        drop(sols);
+        let mut found = [sorted(first.clone()), sorted(second)];
+        found.sort();
+        assert_eq!(found, [vec![0, 1], vec![2]]);
+
+        // A new iterator starts over.
+        assert_eq!(solver.solutions().next(), Some (first.clone()));
+        assert!(found.contains(&sorted(solver.solve_one().unwrap())));
+}
+
+#[test]
+fn search_step_by_step ()
+{
+        // Columns a, b and c have 2, 3 and 3 rows. Branching on a, then b,
+        // the search tries rows 0, 1, 5, 2, 3, 1, 5 and 4.
+        const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        let mut solver = UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c));
+        let mut sols = solver.solutions();
+
+        assert_eq!(sols.step(), Step::Continue);
+        assert_eq!(sols.partial(), [0]);
+        assert_eq!(sols.step(), Step::Continue);
+        assert_eq!(sols.partial(), [0, 1]);
+        assert_eq!(sols.step(), Step::Solution);
+        assert_eq!(sols.partial(), [0, 1]);
+
+        // Stepping and next can be mixed.
+        assert_eq!(sols.next(), Some (vec![0, 5]));
+        assert_eq!(sols.step(), Step::Continue);
+        assert_eq!(sols.partial(), [0]);
+
+        let mut found = Vec::new();
+        let mut steps = 0;
+        loop {
+                steps += 1;
+                match sols.step() {
+                        Step::Continue  => {}
+                        Step::Solution  => found.push(sols.partial()),
+                        Step::Exhausted => break,
+                }
+        }
+        assert_eq!(found, [vec![2, 3, 1], vec![2, 3, 5], vec![2, 4]]);
+        // Eight more moves, three solutions, and the step that tells that we are done.
+        assert_eq!(steps, 12);
+        assert_eq!(sols.partial(), Vec::<usize>::new());
+        assert_eq!(sols.step(), Step::Exhausted);
+}
+
+#[test]
+fn dropped_solutions_restore_the_solver ()
+{
+        const ROWS: usize = 4;
+        const COLS: usize = 3;
+        let rows: Vec<usize> = (0..ROWS).collect();
+        let cols: Vec<usize> = (0..COLS).collect();
+        for cells in 0u32..(1 << (ROWS * COLS)) {
+                let get = |r: usize, c: usize| cells & (1 << (r * COLS + c)) != 0;
+                // The solver does not accept empty rows or columns.
+                let empty_row = rows.iter().any(|&r| cols.iter().all(|&c| !get(r, c)));
+                let empty_col = cols.iter().any(|&c| rows.iter().all(|&r| !get(r, c)));
+                if empty_row || empty_col {
+                        continue;
+                }
+
+                let mut solver = UCSolver::from_pred(&rows, &cols, |&r, &c| get(r, c));
+                let all: Vec<Vec<usize>> = solver.solutions().collect();
+
+                // Dropping the iterator after any number of solutions must have put every row back.
+                for take in 0..=all.len() + 1 {
+                        let first: Vec<Vec<usize>> = solver.solutions().take(take).collect();
+                        assert_eq!(first, all[..take.min(all.len())]);
+                        assert_eq!(solver.solutions().collect::<Vec<_>>(), all);
+                }
+        }
+}
@@ -0,0 +1,138 @@
+// Reading instances in the format of Knuth's DLX programs.
+
+use dancing_links_x::dlx::{Color, ColumnKind, EmptyRows, UCSolver};
+
+fn sorted (mut sol: Vec<String>) -> Vec<String>
+{
+        sol.sort();
+        sol
+}
+
+#[test]
+fn knuths_example ()
+{
+        let text = "\
+| This is the example from the documentation of DLX1.
+A B C D E F G
+C E F
+A D G
+B C F
+A D
+B G
+D E G
+";
+        let mut solver = UCSolver::from_dlx_format(text).unwrap();
+        assert_eq!(solver.count_solutions(None), 1);
+        assert_eq!(sorted(solver.solve_one().unwrap()), ["A D", "B G", "C E F"]);
+}
+
+#[test]
+fn colors_and_bounds ()
+{
+        // X can be shared by options that agree on its color.
+        let text = "\
+A B | X
+A X:red
+B X:red
+
+A X:blue
+";
+        let mut solver = UCSolver::from_dlx_format(text).unwrap();
+        assert_eq!(sorted(solver.solve_one().unwrap()), ["A X:red", "B X:red"]);
+
+        // A has to be covered twice, B once or twice.
+        let text = "\
+2|A 1:2|B
+A
+A B
+B
+";
+        let mut solver = UCSolver::from_dlx_format(text).unwrap();
+        let mut sols: Vec<Vec<String>> = solver.solve_many().into_iter().map(sorted).collect();
+        sols.sort();
+        assert_eq!(sols, [vec!["A", "A B"], vec!["A", "A B", "B"]]);
+        assert!(solver.to_dlx_format().unwrap().starts_with("2:2|A 1:2|B\n"));
+}
+
+#[test]
+fn names_survive_a_round_trip ()
+{
+        let text = "A  B |  X\nA   X:c1\nB X:c2\n";
+        let mut solver = UCSolver::from_dlx_format(text).unwrap();
+        assert_eq!(solver.to_dlx_format().unwrap(), "A B | X\nA X:c1\nB X:c2\n");
+
+        let cols = solver.add_column(ColumnKind::Strict, &["A X:c1".to_string()]);
+        assert_eq!(cols, 3);
+        assert_eq!(solver.to_dlx_format().unwrap(), "A B c3 | X\nA X:c1 c3\nB X:c2\n");
+
+        // A color that was not read gets its number as name.
+        solver.add_colored_row("new".to_string(), &[(1, None), (2, Color::new(3))]);
+        assert_eq!(solver.to_dlx_format().unwrap(), "A B c3 | X\nA X:c1 c3\nB X:c2\nB X:3\n");
+}
+
+#[test]
+fn only_used_colors_are_named ()
+{
+        // Color 1 is called 2 here, so color 2 has to be called something else.
+        let mut solver = UCSolver::from_dlx_format("A | X\nA X:2\n").unwrap();
+        solver.add_colored_row("big".to_string(), &[(0, None), (1, Color::new(u32::MAX))]);
+        solver.add_colored_row("two".to_string(), &[(0, None), (1, Color::new(2))]);
+        assert_eq!(solver.to_dlx_format().unwrap(), "A | X\nA X:2\nA X:4294967295\nA X:2'\n");
+}
+
+#[test]
+fn items_in_no_option ()
+{
+        // B can never be covered.
+        let mut solver = UCSolver::from_dlx_format("A B\nA\n").unwrap();
+        assert_eq!(solver.solve_one(), None);
+        // A line that starts with | is a comment, so X is the only item, and in no option.
+        let mut solver = UCSolver::from_dlx_format("| X\nX\n").unwrap();
+        assert_eq!(solver.count_solutions(None), 0);
+        // Y need not be covered.
+        let mut solver = UCSolver::from_dlx_format("A | X Y\nA X\n").unwrap();
+        assert_eq!(solver.solve_one().unwrap(), ["A X"]);
+        assert_eq!(solver.to_dlx_format().unwrap(), "A | X Y\nA X\n");
+}
+
+#[test]
+fn only_optional_columns_are_not_written ()
+{
+        use std::io::ErrorKind;
+
+        // The header would start with |, which reads back as a comment.
+        let rows = ["x".to_string(), "y".to_string()];
+        let solver = UCSolver::from_pred_opt(&rows, &[], &[0], |_, _| true);
+        let mut out = Vec::new();
+        assert_eq!(solver.write_dlx_format(&mut out).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert!(out.is_empty());
+        assert_eq!(solver.to_dlx_format().unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // Neither is a solver without any columns.
+        let solver = UCSolver::try_from_pred(&rows, &[0; 0], |_, _| true, EmptyRows::Ignore).unwrap();
+        assert_eq!(solver.write_dlx_format(&mut out).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // With a strict column, what was written reads back the same.
+        let solver = UCSolver::from_pred_opt(&rows, &[0], &[1], |r, &c| c == 0 || r == "x");
+        let text = solver.to_dlx_format().unwrap();
+        assert_eq!(text, "c0 | c1\nc0 c1\nc0\n");
+        let mut parsed = UCSolver::from_dlx_format(&text).unwrap();
+        assert_eq!(parsed.count_solutions(None), 2);
+        assert_eq!(parsed.to_dlx_format().unwrap(), text);
+}
+
+#[test]
+fn errors_point_at_the_line ()
+{
+        let line_of = |text: &str| UCSolver::from_dlx_format(text).err().unwrap().line;
+        assert_eq!(line_of(""), 1);
+        assert_eq!(line_of("| only a comment\n| B\n"), 1);
+        assert_eq!(line_of("A B\nA\nB C\n"), 3);
+        assert_eq!(line_of("A | X\nA:red X\n"), 2);
+        assert_eq!(line_of("A | X\nA X X\n"), 2);
+        assert_eq!(line_of("A A\nA\n"), 1);
+        assert_eq!(line_of("0|A\nA\n"), 1);
+        assert_eq!(line_of("A | 2|X\nA X\n"), 1);
+        let err = UCSolver::from_dlx_format("A\nB\n").err().unwrap();
+        assert_eq!(err.to_string(), "line 2: Unknown item B");
+}
@@ -0,0 +1,101 @@
+// Node, deadline and cancellation limits, on an instance small enough to count the nodes by hand.
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use dancing_links_x::dlx::{DlxError, Limits, Outcome, UCSolver};
+
+// Columns a, b and c have 2, 3 and 3 rows. Branching on a, then b,
+// the full search tries rows 0, 1, 5, 2, 3, 1, 5 and 4: eight nodes for five solutions.
+// The first solution, {0, 1}, takes two nodes.
+fn solver () -> UCSolver<usize>
+{
+        const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c))
+}
+
+fn sorted <T: Ord> (mut sol: Vec<T>) -> Vec<T>
+{
+        sol.sort();
+        sol
+}
+
+#[test]
+fn node_limits ()
+{
+        let mut solver = solver();
+        let limits = |max_nodes| Limits::new().with_max_nodes(max_nodes);
+
+        assert_eq!(solver.solve_one_limited(&limits(1)), Outcome::Aborted);
+        assert_eq!(solver.solve_one_limited(&limits(2)).map(sorted), Outcome::Solved(vec![0, 1]));
+
+        assert_eq!(solver.solve_many_limited(&limits(7)), Outcome::Aborted);
+        let sols = solver.solve_many_limited(&limits(8)).map(|sols| sorted(sols.into_iter().map(sorted).collect()));
+        assert_eq!(sols, Outcome::Solved(vec![vec![0, 1], vec![0, 5], vec![1, 2, 3], vec![2, 3, 5], vec![2, 4]]));
+
+        assert_eq!(solver.count_solutions_limited(None, &limits(7)), Outcome::Aborted);
+        assert_eq!(solver.count_solutions_limited(None, &limits(8)), Outcome::Solved(5));
+        // A cap can end the search before the budget runs out.
+        assert_eq!(solver.count_solutions_limited(Some (1), &limits(2)), Outcome::Solved(1));
+
+        // An aborted search leaves the solver as it was.
+        assert_eq!(solver.count_solutions(None), 5);
+}
+
+#[test]
+fn unsatisfiable_within_limits ()
+{
+        // Row 0 is the only row with 'b' and row 1 the only one with 'c', but both have 'a'.
+        let mut solver = UCSolver::from_pred(&[0, 1], &['a', 'b', 'c'], |&r, &c| c == 'a' || (r == 0) == (c == 'b'));
+        let limits = Limits::new().with_max_nodes(1);
+        assert_eq!(solver.solve_one_limited(&limits), Outcome::Unsatisfiable);
+        assert_eq!(solver.solve_many_limited(&limits), Outcome::Unsatisfiable);
+        assert_eq!(solver.count_solutions_limited(None, &limits), Outcome::Unsatisfiable);
+}
+
+#[test]
+fn forced_rows_within_limits ()
+{
+        // With row 2 forced, branching on b tries rows 3, 1, 5 and 4:
+        // four nodes for three solutions, the first of which takes two.
+        let mut solver = solver();
+        let limits = |max_nodes| Limits::new().with_max_nodes(max_nodes);
+        assert_eq!(solver.solve_one_with_limited(&[2], &limits(1)), Outcome::Aborted);
+        assert_eq!(solver.solve_one_with_limited(&[2], &limits(2)).map(sorted), Outcome::Solved(vec![1, 3]));
+
+        assert_eq!(solver.solve_many_with_limited(&[2], &limits(3)), Outcome::Aborted);
+        let sols = solver.solve_many_with_limited(&[2], &limits(4)).map(|sols| sorted(sols.into_iter().map(sorted).collect()));
+        assert_eq!(sols, Outcome::Solved(vec![vec![1, 3], vec![3, 5], vec![4]]));
+
+        assert_eq!(solver.count_solutions_with_limited(&[2], None, &limits(3)), Outcome::Aborted);
+        assert_eq!(solver.count_solutions_with_limited(&[2], None, &limits(4)), Outcome::Solved(3));
+
+        // Rows 0 and 2 both have 'a', so forcing both leaves nothing to search.
+        assert_eq!(solver.solve_one_with_limited(&[0, 2], &limits(0)), Outcome::Unsatisfiable);
+        assert_eq!(solver.solve_many_with_limited(&[0, 2], &limits(0)), Outcome::Unsatisfiable);
+        assert_eq!(solver.count_solutions_with_limited(&[0, 2], None, &limits(0)), Outcome::Unsatisfiable);
+        assert_eq!(solver.try_count_solutions_with_limited(&[9], None, &limits(4)), Err (DlxError::UnknownRow));
+
+        // The forced rows are recovered, whether the search was aborted or not.
+        assert_eq!(solver.count_solutions(None), 5);
+}
+
+#[test]
+fn cancelled_and_timed_out ()
+{
+        let mut solver = solver();
+        let cancel = Arc::new(AtomicBool::new(true));
+        let cancelled = Limits::new().with_cancel(Arc::clone(&cancel));
+        assert_eq!(solver.solve_one_limited(&cancelled), Outcome::Aborted);
+        assert_eq!(solver.count_solutions_limited(None, &cancelled), Outcome::Aborted);
+
+        cancel.store(false, std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(solver.count_solutions_limited(None, &cancelled), Outcome::Solved(5));
+
+        let past = Limits::new().with_deadline(Instant::now());
+        assert_eq!(solver.solve_many_limited(&past), Outcome::Aborted);
+        let far = Limits::new().with_timeout(Duration::from_secs(3600));
+        assert_eq!(solver.count_solutions_limited(None, &far), Outcome::Solved(5));
+}
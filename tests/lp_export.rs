@@ -0,0 +1,127 @@
+// Writing instances for integer programming solvers.
+
+use dancing_links_x::dlx::{Color, UCSolver};
+
+#[test]
+fn colored_lp ()
+{
+        // X is shared by a and b, which give it the same color.
+        let rows = ["a", "b", "c", "d"];
+        let cells = [[1, 0, 1], [0, 1, 1], [1, 1, 0], [0, 1, 2]];
+        let idx = |r: &&str| rows.iter().position(|x| x == r).unwrap();
+        let solver = UCSolver::from_pred_colored(&rows, &[0, 1], &[2], |r, &c| cells[idx(r)][c] != 0, |r, &c| Color::new(cells[idx(r)][c]));
+        let (lp, table) = solver.to_lp(|r| if *r == "c" {2.5} else {1.0});
+        assert_eq!(table, rows);
+        assert_eq!(lp, "\
+\\ The 4 rows are x0 onwards.
+Minimize
+ obj: + 1 x0 + 1 x1 + 2.5 x2 + 1 x3 + 0 y2_1 + 0 y2_2
+Subject To
+ c0: + 1 x0 + 1 x2 = 1
+ c1: + 1 x1 + 1 x2 + 1 x3 = 1
+ c2_x0: + 1 x0 - 1 y2_1 <= 0
+ c2_x1: + 1 x1 - 1 y2_1 <= 0
+ c2_x3: + 1 x3 - 1 y2_2 <= 0
+ c2: + 1 y2_1 + 1 y2_2 <= 1
+Binaries
+ x0
+ x1
+ x2
+ x3
+ y2_1
+ y2_2
+End
+");
+}
+
+#[test]
+fn bounds_and_deleted_rows ()
+{
+        // Column 0 is covered once or twice, column 1 at most once,
+        // and row 3 only has the optional column 2, so it is never chosen.
+        let rows = [0, 1, 2, 3];
+        let cells = [[1, 0, 0], [1, 1, 0], [0, 1, 1], [0, 0, 1]];
+        let mut solver = UCSolver::from_pred_bounded(&rows, &[0, 1], &[(1, 2), (0, 1)], &[2], |&r, &c| cells[r][c] == 1);
+        solver.delete_row(&2);
+        let (lp, table) = solver.to_lp(|&r| r as f64);
+        assert_eq!(table, [0, 1, 3]);
+        assert_eq!(lp, "\
+\\ The 3 rows are x0 onwards.
+Minimize
+ obj: + 0 x0 + 1 x1 + 3 x2
+Subject To
+ c0: + 1 x0 + 1 x1 >= 1
+ c0_hi: + 1 x0 + 1 x1 <= 2
+ c1: + 1 x1 <= 1
+ c2: + 1 x2 <= 1
+Bounds
+ x2 = 0
+Binaries
+ x0
+ x1
+End
+");
+
+        let (mps, table) = solver.to_mps(|_| 1.0);
+        assert_eq!(table, [0, 1, 3]);
+        assert!(mps.contains("ROWS\n N obj\n G c0\n L c1\n L c2\n"));
+        assert!(mps.contains("RANGES\n    RNG c0 1\n"));
+        assert!(mps.contains("BOUNDS\n BV BND x0\n BV BND x1\n FX BND x2 0\nENDATA\n"));
+}
+
+#[test]
+fn fixed_rows_are_not_binaries ()
+{
+        // Row 1 has only the optional column, so it is fixed to 0, and only bounded.
+        let solver = UCSolver::from_pred_opt(&[0, 1], &[0], &[1], |&r, &c| r == 0 || c == 1);
+        let (lp, _) = solver.to_lp(|_| 1.0);
+        let bounds = &lp[lp.find("Bounds\n").unwrap()..lp.find("Binaries\n").unwrap()];
+        let binaries = &lp[lp.find("Binaries\n").unwrap()..];
+        assert_eq!(bounds, "Bounds\n x1 = 0\n");
+        assert_eq!(binaries, "Binaries\n x0\nEnd\n");
+}
+
+#[test]
+fn empty_columns ()
+{
+        // Nothing covers strict column 1 or optional column 2.
+        let solver = UCSolver::from_pred_opt(&[0], &[0, 1], &[2], |_, &c| c == 0);
+        let (lp, _) = solver.to_lp(|_| 1.0);
+        assert_eq!(lp, "\
+\\ The 1 rows are x0 onwards.
+Minimize
+ obj: + 1 x0
+Subject To
+ c0: + 1 x0 = 1
+ c1: 0 x0 = 1
+ c2: 0 x0 <= 1
+Binaries
+ x0
+End
+");
+}
+
+#[test]
+fn no_rows ()
+{
+        // All rows are deleted, so only the stand-in variable is left.
+        let mut solver = UCSolver::from_pred_opt(&[0], &[0], &[1], |_, _| true);
+        solver.delete_row(&0);
+        let (lp, table) = solver.to_lp(|_| 1.0);
+        assert!(table.is_empty());
+        assert_eq!(lp, "\
+\\ The 0 rows are x0 onwards.
+Minimize
+ obj: + 0 z
+Subject To
+ c0: 0 z = 1
+ c1: 0 z <= 1
+Bounds
+ z = 0
+End
+");
+
+        let (mps, _) = solver.to_mps(|_| 1.0);
+        assert!(mps.contains("COLUMNS\n    MARKER 'MARKER' 'INTORG'\n    z obj 0\n    MARKER 'MARKER' 'INTEND'\n"));
+        assert!(mps.contains("BOUNDS\n FX BND z 0\nENDATA\n"));
+}
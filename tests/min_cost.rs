@@ -0,0 +1,57 @@
+// Minimum-cost exact cover, on an instance where all solutions can be priced by hand.
+
+use dancing_links_x::dlx::UCSolver;
+
+// The five solutions are {0, 1}, {0, 5}, {1, 2, 3}, {2, 3, 5} and {2, 4},
+// and {0, 1} is the first one found.
+fn solver () -> UCSolver<usize>
+{
+        const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c))
+}
+
+fn sorted ((mut sol, cost): (Vec<usize>, u64)) -> (Vec<usize>, u64)
+{
+        sol.sort();
+        (sol, cost)
+}
+
+#[test]
+fn fewest_rows_without_costs ()
+{
+        // {0, 1}, {0, 5} and {2, 4} all have two rows, and {0, 1} is found first.
+        assert_eq!(solver().solve_min_cost().map(sorted), Some ((vec![0, 1], 2)));
+}
+
+#[test]
+fn cheapest_solution ()
+{
+        // The solutions cost 6, 8, 3, 5 and 2.
+        const COSTS: [u64; 6] = [5, 1, 1, 1, 1, 3];
+        let mut solver = solver().with_row_costs(|&r| COSTS[r]);
+        assert_eq!(solver.solve_min_cost().map(sorted), Some ((vec![2, 4], 2)));
+
+        // The solutions cost 2, 2, 6, 6 and 13, so the tie goes to the first one.
+        const TIED: [u64; 6] = [1, 1, 4, 1, 9, 1];
+        let mut solver = self::solver().with_row_costs(|&r| TIED[r]);
+        assert_eq!(solver.solve_min_cost().map(sorted), Some ((vec![0, 1], 2)));
+
+        // The search leaves the solver as it was.
+        assert_eq!(solver.count_solutions(None), 5);
+}
+
+#[test]
+fn zero_costs ()
+{
+        let mut solver = solver().with_row_costs(|&r| if r == 4 {0} else {10});
+        assert_eq!(solver.solve_min_cost().map(sorted), Some ((vec![2, 4], 10)));
+}
+
+#[test]
+fn no_solution_no_cost ()
+{
+        // Row 0 is the only row with 'b' and row 1 the only one with 'c', but both have 'a'.
+        let mut solver = UCSolver::from_pred(&[0, 1], &['a', 'b', 'c'], |&r, &c| c == 'a' || (r == 0) == (c == 'b'));
+        assert_eq!(solver.solve_min_cost(), None);
+}
@@ -0,0 +1,88 @@
+// Changing the rows and columns of a solver between searches.
+
+use dancing_links_x::dlx::{ColumnKind, UCSolver};
+
+// The five solutions are {0, 1}, {0, 5}, {1, 2, 3}, {2, 3, 5} and {2, 4}.
+fn solver () -> UCSolver<usize>
+{
+        const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c))
+}
+
+fn all (solver: &mut UCSolver<usize>) -> Vec<Vec<usize>>
+{
+        let mut sols = solver.solve_many();
+        for sol in &mut sols {
+                sol.sort();
+        }
+        sols.sort();
+        sols
+}
+
+#[test]
+fn add_and_delete_rows ()
+{
+        let mut solver = solver();
+
+        // A row with all three columns is a solution on its own.
+        solver.add_row(6, &[0, 1, 2]);
+        assert_eq!(all(&mut solver), [vec![0, 1], vec![0, 5], vec![1, 2, 3], vec![2, 3, 5], vec![2, 4], vec![6]]);
+
+        // Without row 0, only the solutions with row 2 are left, and row 6.
+        solver.delete_row(&0);
+        assert_eq!(all(&mut solver), [vec![1, 2, 3], vec![2, 3, 5], vec![2, 4], vec![6]]);
+        assert_eq!(solver.count_solutions(None), 4);
+
+        // Row 7 replaces row 0.
+        solver.add_row(7, &[0, 1]);
+        assert_eq!(all(&mut solver), [vec![1, 2, 3], vec![1, 7], vec![2, 3, 5], vec![2, 4], vec![5, 7], vec![6]]);
+        assert_eq!(solver.solve_one_with(&[7, 5]), Some (vec![]));
+}
+
+#[test]
+fn delete_the_only_row_of_a_column ()
+{
+        let mut solver = solver();
+        solver.delete_row(&2);
+        solver.delete_row(&0);
+        // Column a cannot be covered anymore.
+        assert_eq!(solver.solve_one(), None);
+        assert_eq!(solver.count_solutions(None), 0);
+
+        solver.add_row(6, &[0]);
+        assert_eq!(all(&mut solver), [vec![1, 3, 6], vec![3, 5, 6], vec![4, 6]]);
+}
+
+#[test]
+fn add_columns ()
+{
+        let mut solver = solver();
+
+        // Only the solutions with exactly one of rows 1 and 4 are left.
+        assert_eq!(solver.add_column(ColumnKind::Strict, &[1, 4]), 3);
+        assert_eq!(all(&mut solver), [vec![0, 1], vec![1, 2, 3], vec![2, 4]]);
+
+        // Rows 1 and 2 may no longer go together.
+        assert_eq!(solver.add_column(ColumnKind::Optional, &[1, 2]), 4);
+        assert_eq!(all(&mut solver), [vec![0, 1], vec![2, 4]]);
+
+        // New rows can use the new columns.
+        solver.add_row(6, &[0, 1, 2, 3, 4]);
+        solver.add_row(7, &[0, 1, 2, 3]);
+        assert_eq!(all(&mut solver), [vec![0, 1], vec![2, 4], vec![6], vec![7]]);
+        assert_eq!(solver.count_solutions_with(&[7], None), 1);
+}
+
+#[test]
+fn add_a_column_nobody_can_cover ()
+{
+        let mut solver = solver();
+        solver.add_column(ColumnKind::Strict, &[]);
+        assert_eq!(solver.solve_one(), None);
+
+        // An optional column without rows changes nothing.
+        let mut solver = self::solver();
+        solver.add_column(ColumnKind::Optional, &[]);
+        assert_eq!(solver.count_solutions(None), 5);
+}
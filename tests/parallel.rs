@@ -0,0 +1,61 @@
+// Parallel search, on the set partitions of a few elements.
+
+use dancing_links_x::dlx::UCSolver;
+
+// The rows are all nonempty subsets of n columns, so the solutions are
+// the set partitions of n elements, counted by the Bell numbers.
+fn partitions (n: u32) -> UCSolver<u32>
+{
+        let rows: Vec<u32> = (1..1 << n).collect();
+        let cols: Vec<u32> = (0..n).collect();
+        UCSolver::from_pred(&rows, &cols, |&r, &c| r & (1 << c) != 0)
+}
+
+#[test]
+fn par_count_partitions ()
+{
+        let mut solver = partitions(5);
+        // Depth 0 is a single piece of work, and 5 is deeper than any solution.
+        for split_depth in 0..=5 {
+                assert_eq!(solver.par_count(split_depth), 52);
+        }
+        assert_eq!(partitions(6).par_count(2), 203);
+}
+
+#[test]
+fn par_solve_many_keeps_the_serial_order ()
+{
+        let mut solver = partitions(4);
+        let serial = solver.solve_many();
+        assert_eq!(serial.len(), 15);
+        for split_depth in 0..=4 {
+                assert_eq!(solver.par_solve_many(split_depth), serial);
+        }
+}
+
+#[test]
+fn par_solve_one_finds_a_partition ()
+{
+        let mut solver = partitions(5);
+        for split_depth in 0..=3 {
+                let sol = solver.par_solve_one(split_depth).unwrap();
+                assert_eq!(sol.iter().fold(0, |acc, &r| {
+                        assert_eq!(acc & r, 0);
+                        acc | r
+                }), 0b11111);
+        }
+        // The solver is left as it was.
+        assert_eq!(solver.count_solutions(None), 52);
+}
+
+#[test]
+fn par_search_without_solutions ()
+{
+        // Row 0 is the only row with 'b' and row 1 the only one with 'c', but both have 'a'.
+        let mut solver = UCSolver::from_pred(&[0, 1], &['a', 'b', 'c'], |&r, &c| c == 'a' || (r == 0) == (c == 'b'));
+        for split_depth in 0..=2 {
+                assert_eq!(solver.par_count(split_depth), 0);
+                assert_eq!(solver.par_solve_one(split_depth), None);
+                assert!(solver.par_solve_many(split_depth).is_empty());
+        }
+}
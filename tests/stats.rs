@@ -0,0 +1,104 @@
+// Search statistics and the progress callback, on an instance small enough to trace by hand.
+
+use std::num::NonZeroU64;
+use std::sync::{Arc, Mutex};
+
+use dancing_links_x::dlx::{Color, Limits, Outcome, UCSolver};
+
+// Columns a, b and c have 2, 3 and 3 rows. Branching on a, then b,
+// the full search tries rows 0, 1, 5, 2, 3, 1, 5 and 4, with 0 and 2 at depth 0,
+// 1, 5, 3 and 4 at depth 1, and the last 1 and 5 at depth 2.
+// The first solution, {0, 1}, takes two nodes.
+fn solver () -> UCSolver<usize>
+{
+        const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c))
+}
+
+#[test]
+fn stats_of_a_full_search ()
+{
+        let mut solver = solver();
+        assert_eq!(solver.count_solutions(None), 5);
+        let stats = solver.stats();
+        assert_eq!(stats.nodes, 8);
+        assert_eq!(stats.solutions, 5);
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.nodes_per_depth, [2, 4, 2]);
+        assert!(stats.updates > 0);
+
+        // A new search starts from zero.
+        solver.solve_one();
+        assert_eq!(solver.stats().nodes, 2);
+        assert_eq!(solver.stats().solutions, 1);
+        assert_eq!(solver.stats().nodes_per_depth, [1, 1]);
+}
+
+#[test]
+fn stats_of_an_iterator ()
+{
+        let mut solver = solver();
+        let mut sols = solver.solutions();
+        sols.next();
+        assert_eq!(sols.stats().nodes, 2);
+        assert_eq!(sols.stats().solutions, 1);
+        sols.next();
+        sols.next();
+        assert_eq!(sols.stats().nodes, 6);
+        assert_eq!(sols.stats().solutions, 3);
+        drop(sols);
+        // Putting the rows back adds updates, but no nodes.
+        assert_eq!(solver.stats().nodes, 6);
+        assert_eq!(solver.stats().solutions, 3);
+}
+
+#[test]
+fn progress_every_three_nodes ()
+{
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&calls);
+        let mut solver = solver();
+        solver.set_progress(NonZeroU64::new(3).unwrap(), move |depth, rows: &[usize]| {
+                log.lock().unwrap().push((depth, rows.to_vec()));
+        });
+        assert_eq!(solver.count_solutions(None), 5);
+        // The third node is row 5 after row 0, the sixth is row 1 after rows 2 and 3.
+        assert_eq!(*calls.lock().unwrap(), [(2, vec![0, 5]), (3, vec![2, 3, 1])]);
+
+        calls.lock().unwrap().clear();
+        solver.clear_progress();
+        solver.count_solutions(None);
+        assert!(calls.lock().unwrap().is_empty());
+}
+
+#[test]
+fn updates_with_colors ()
+{
+        // Rows "p x:A", "q x:A", "q x:B" and "q", with p and q strict and x optional.
+        // Choosing row 0 covers p, which unlinks its header and the x node of row 0,
+        // and purifies x to A, which hides the q node of row 2: 3 updates.
+        // Row 1 then covers q, which unlinks its header and the x node of row 1: 2 updates,
+        // and x is already purified. Taking row 1 back relinks those 2,
+        // and row 3 covers q again, unlinking the same 2, as row 3 has no other node.
+        // Taking back row 3 and then row 0 relinks 2 and 3 more: 14 in all.
+        let rows = [0, 1, 2, 3];
+        let has = |&r: &usize, &c: &char| match c {
+                'p' => r == 0,
+                'q' => r > 0,
+                _ => r < 3,
+        };
+        let color = |&r: &usize, _: &char| Color::new(if r < 2 {1} else {2});
+        let mut solver = UCSolver::from_pred_colored(&rows, &['p', 'q'], &['x'], has, color);
+        assert_eq!(solver.count_solutions(None), 2);
+        assert_eq!(solver.stats().nodes, 3);
+        assert_eq!(solver.stats().updates, 14);
+
+        // With two nodes, the search stops right before it would choose row 3.
+        // Taking back row 1 to look for that choice, and choosing row 1 again, does not count.
+        // Only the 5 updates of rows 0 and 1 are, and the 5 of putting them back.
+        let limits = Limits::new().with_max_nodes(2);
+        assert_eq!(solver.count_solutions_limited(None, &limits), Outcome::Aborted);
+        assert_eq!(solver.stats().nodes, 2);
+        assert_eq!(solver.stats().updates, 10);
+}
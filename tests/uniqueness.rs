@@ -0,0 +1,55 @@
+// Telling unique solutions apart from several, with the witnesses that show it.
+
+use dancing_links_x::dlx::{Uniqueness, UCSolver};
+
+// The five solutions are {0, 1}, {0, 5}, {1, 2, 3}, {2, 3, 5} and {2, 4},
+// found in that order.
+fn solver () -> UCSolver<usize>
+{
+        const ROWS: [&str; 6] = ["ab", "c", "a", "b", "bc", "c"];
+        let rows: Vec<usize> = (0..ROWS.len()).collect();
+        UCSolver::from_pred(&rows, &['a', 'b', 'c'], |&r, &c| ROWS[r].contains(c))
+}
+
+fn sorted (uniqueness: Uniqueness<usize>) -> Uniqueness<usize>
+{
+        let sort = |mut sol: Vec<usize>| {
+                sol.sort();
+                sol
+        };
+        match uniqueness {
+                Uniqueness::None                        => Uniqueness::None,
+                Uniqueness::Unique(sol)                 => Uniqueness::Unique(sort(sol)),
+                Uniqueness::Multiple(first, second)     => Uniqueness::Multiple(sort(first), sort(second)),
+        }
+}
+
+#[test]
+fn the_first_two_solutions_are_witnesses ()
+{
+        let mut solver = solver();
+        assert_eq!(sorted(solver.uniqueness()), Uniqueness::Multiple(vec![0, 1], vec![0, 5]));
+        // The check leaves the solver as it was.
+        assert_eq!(solver.count_solutions(None), 5);
+}
+
+#[test]
+fn uniqueness_with_forced_rows ()
+{
+        let mut solver = solver();
+        // Only row 2 goes with row 4.
+        assert_eq!(sorted(solver.uniqueness_with(&[4])), Uniqueness::Unique(vec![2]));
+        // Rows 2 and 3 leave c, which rows 1 and 5 both cover.
+        assert_eq!(sorted(solver.uniqueness_with(&[2, 3])), Uniqueness::Multiple(vec![1], vec![5]));
+        // Rows 0 and 1 are a solution on their own, and nothing can be added.
+        assert_eq!(solver.uniqueness_with(&[0, 1]), Uniqueness::Unique(vec![]));
+        assert_eq!(solver.count_solutions(None), 5);
+}
+
+#[test]
+fn no_solution ()
+{
+        // Row 0 is the only row with 'b' and row 1 the only one with 'c', but both have 'a'.
+        let mut solver = UCSolver::from_pred(&[0, 1], &['a', 'b', 'c'], |&r, &c| c == 'a' || (r == 0) == (c == 'b'));
+        assert_eq!(solver.uniqueness(), Uniqueness::None);
+}